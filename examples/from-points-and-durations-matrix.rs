@@ -1,5 +1,5 @@
 use distance_cartogram::{
-    adjustment, generate_positions_from_durations, procrustes, utils, BBox, Grid,
+    adjustment, generate_positions_from_durations, procrustes, utils, BBox, Grid, Symmetrization,
 };
 use geo_types::Coord;
 use geojson::{Feature, FeatureCollection, GeoJson, Geometry, Value};
@@ -17,9 +17,11 @@ fn main() {
     let geojson_source = read_geojson(path_source);
     let geojson_background = read_geojson(path_layer_to_deform);
 
-    let (durations, _ids) = utils::read_csv(
+    let (durations, labels) = utils::read_csv(
         std::fs::File::open(path_durations).expect("Unable to open file of durations"),
-    );
+        &utils::CsvOptions::default(),
+    )
+    .expect("Unable to read the durations matrix");
 
     // Read the background layer.
     // We want to read the foreign members of the GeoJson FeatureCollection
@@ -34,10 +36,18 @@ fn main() {
 
     // Extract source points to a Vec<Coord>
     let mut points_source = Vec::new();
+    let mut ids_source = Vec::new();
     match geojson_source {
         GeoJson::FeatureCollection(collection) => {
             let fts = collection.features;
             for feature in fts {
+                ids_source.push(
+                    feature
+                        .property("NOM_COM")
+                        .and_then(|v| v.as_str())
+                        .expect("Expected a NOM_COM property")
+                        .to_string(),
+                );
                 let geometry = feature.geometry.unwrap();
                 let coordinates = geometry.value;
                 match coordinates {
@@ -54,8 +64,12 @@ fn main() {
         _ => panic!("Expected a feature collection"),
     };
 
+    // Make sure that the order of the durations matrix matches the order of the source points
+    let durations = utils::join_durations_to_points(&durations, &labels, &ids_source)
+        .expect("Unable to match the durations matrix to the source points");
+
     let t = Instant::now();
-    let mds_points = generate_positions_from_durations(durations.clone())
+    let mds_points = generate_positions_from_durations(durations.clone(), Symmetrization::Mean)
         .expect("Unable to generate positions from durations");
     println!("Generating points from durations matrix: {:?}", t.elapsed());
 
@@ -144,7 +158,7 @@ fn main() {
 
     // Write the GeoJson to a file, taking care to transferring the original properties
     let mut features = Vec::new();
-    for (polygon, props) in bg_transformed.into_iter().zip(props_bg_layer.into_iter()) {
+    for (polygon, props) in bg_transformed.into_iter().zip(props_bg_layer) {
         let geometry = Geometry::new(geojson::Value::from(&polygon));
         let feature = Feature {
             bbox: None,
@@ -190,16 +204,16 @@ fn create_fc_from_coords(pts: &[Coord], fm: Option<geojson::JsonObject>) -> Feat
 
 fn save_to_file(feature_collection: &FeatureCollection, path: &str) {
     std::fs::File::create(path)
-        .expect(format!("Unable to create file {}", path).as_str())
+        .unwrap_or_else(|_| panic!("Unable to create file {}", path))
         .write_all(feature_collection.to_string().as_bytes())
-        .expect(format!("Unable to write file {}", path).as_str());
+        .unwrap_or_else(|_| panic!("Unable to write file {}", path));
 }
 
 fn read_geojson(path: &str) -> GeoJson {
     let file_source =
-        std::fs::File::open(path).expect(format!("Unable to open file '{}'", path).as_str());
+        std::fs::File::open(path).unwrap_or_else(|_| panic!("Unable to open file '{}'", path));
 
-    GeoJson::from_reader(&file_source).expect(format!("Unable to read file '{}'", path).as_str())
+    GeoJson::from_reader(&file_source).unwrap_or_else(|_| panic!("Unable to read file '{}'", path))
 }
 
 fn read_crs(geojson_layer: &GeoJson) -> Option<geojson::JsonObject> {
@@ -146,7 +146,7 @@ fn main() {
 
     // Write the GeoJson to a file, taking care to transferring the original properties
     let mut features = Vec::new();
-    for (polygon, props) in bg_transformed.into_iter().zip(props_bg_layer.into_iter()) {
+    for (polygon, props) in bg_transformed.into_iter().zip(props_bg_layer) {
         let geometry = Geometry::new(geojson::Value::from(&polygon));
         let feature = Feature {
             bbox: None,
@@ -223,7 +223,7 @@ fn create_fc_from_coords(pts: &[Coord], fm: Option<geojson::JsonObject>) -> Feat
 
 fn save_to_file(feature_collection: &FeatureCollection, path: &str) {
     std::fs::File::create(path)
-        .expect(format!("Unable to create file {}", path).as_str())
+        .unwrap_or_else(|_| panic!("Unable to create file {}", path))
         .write_all(feature_collection.to_string().as_bytes())
-        .expect(format!("Unable to write file {}", path).as_str());
+        .unwrap_or_else(|_| panic!("Unable to write file {}", path));
 }
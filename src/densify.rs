@@ -0,0 +1,294 @@
+use crate::utils::distance_sq;
+use geo_types::Coord;
+
+/// The maximum number of vertices inserted in a segment by the
+/// [`Densification::MaxSpacing`] and [`Densification::ResolutionFraction`] methods.
+const MAX_INSERTED_VERTICES: usize = 10_000;
+
+/// The maximum depth of recursion of the [`Densification::Adaptive`] method
+/// (which inserts at most `2^max_depth - 1` vertices in a segment).
+const MAX_ADAPTIVE_DEPTH: usize = 16;
+
+/// How the segments of the geometries are densified before being deformed
+/// (see [`Grid::interpolate_layer_densified`](crate::Grid::interpolate_layer_densified)),
+/// so that a long straight segment (such as a border or a graticule line)
+/// follows the deformation of the grid instead of staying straight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Densification {
+    /// Insert vertices so that the spacing between two consecutive vertices
+    /// is at most the given distance (in the unit of the coordinates),
+    /// with at most 10 000 vertices inserted in each segment.
+    MaxSpacing(f64),
+    /// Insert vertices so that the spacing between two consecutive vertices is
+    /// at most the given fraction of the resolution of the grid
+    /// (for example 0.25 to have at least 4 vertices per cell crossed by a segment),
+    /// with at most 10 000 vertices inserted in each segment.
+    ResolutionFraction(f64),
+    /// Recursively insert the middle of each segment as long as its deformed
+    /// position deviates from the middle of the deformed segment by more than
+    /// the tolerance (in the unit of the coordinates), up to the given
+    /// maximum depth of recursion (which can't exceed 16).
+    Adaptive { tolerance: f64, max_depth: usize },
+}
+
+impl Densification {
+    /// Whether the parameters of the densification are valid.
+    pub(crate) fn is_valid(&self) -> bool {
+        match *self {
+            Densification::MaxSpacing(d) | Densification::ResolutionFraction(d) => {
+                d.is_finite() && d > 0.
+            }
+            Densification::Adaptive {
+                tolerance,
+                max_depth,
+            } => tolerance.is_finite() && tolerance > 0. && max_depth <= MAX_ADAPTIVE_DEPTH,
+        }
+    }
+}
+
+/// Deform the vertices of a line (using the `deform` function),
+/// densifying its segments beforehand if needed.
+pub(crate) fn deform_line<F>(
+    coords: &[Coord],
+    deform: F,
+    densification: Option<Densification>,
+    resolution: f64,
+) -> Vec<Coord>
+where
+    F: Fn(&Coord) -> Coord,
+{
+    let deformed = coords.iter().map(&deform).collect::<Vec<_>>();
+    let max_spacing = match densification {
+        None => return deformed,
+        Some(Densification::MaxSpacing(d)) => Some(d),
+        Some(Densification::ResolutionFraction(f)) => Some(f * resolution),
+        Some(Densification::Adaptive { .. }) => None,
+    };
+    let mut result = Vec::with_capacity(coords.len());
+    for (k, (a, b)) in coords.iter().zip(coords.iter().skip(1)).enumerate() {
+        result.push(deformed[k]);
+        match (max_spacing, densification) {
+            (Some(spacing), _) => {
+                let n = (distance_sq(a, b).sqrt() / spacing)
+                    .ceil()
+                    .min((MAX_INSERTED_VERTICES + 1) as f64) as usize;
+                for s in 1..n {
+                    let t = s as f64 / n as f64;
+                    result.push(deform(&Coord {
+                        x: a.x + t * (b.x - a.x),
+                        y: a.y + t * (b.y - a.y),
+                    }));
+                }
+            }
+            (
+                None,
+                Some(Densification::Adaptive {
+                    tolerance,
+                    max_depth,
+                }),
+            ) => {
+                subdivide(
+                    (a, &deformed[k]),
+                    (b, &deformed[k + 1]),
+                    &deform,
+                    tolerance * tolerance,
+                    max_depth,
+                    &mut result,
+                );
+            }
+            _ => {}
+        }
+    }
+    if let Some(last) = deformed.last() {
+        result.push(*last);
+    }
+    result
+}
+
+/// Recursively insert the (deformed) middle of the segment between a and b
+/// while it deviates too much from the middle of the deformed segment.
+fn subdivide<F>(
+    a: (&Coord, &Coord),
+    b: (&Coord, &Coord),
+    deform: &F,
+    tolerance_sq: f64,
+    depth: usize,
+    result: &mut Vec<Coord>,
+) where
+    F: Fn(&Coord) -> Coord,
+{
+    if depth == 0 {
+        return;
+    }
+    let middle = Coord {
+        x: (a.0.x + b.0.x) / 2.,
+        y: (a.0.y + b.0.y) / 2.,
+    };
+    let deformed_middle = deform(&middle);
+    let deviation = distance_sq(
+        &deformed_middle,
+        &Coord {
+            x: (a.1.x + b.1.x) / 2.,
+            y: (a.1.y + b.1.y) / 2.,
+        },
+    );
+    if deviation > tolerance_sq {
+        subdivide(
+            a,
+            (&middle, &deformed_middle),
+            deform,
+            tolerance_sq,
+            depth - 1,
+            result,
+        );
+        result.push(deformed_middle);
+        subdivide(
+            (&middle, &deformed_middle),
+            b,
+            deform,
+            tolerance_sq,
+            depth - 1,
+            result,
+        );
+    }
+}
+
+/// Simplify a line using the Douglas-Peucker algorithm, removing the vertices
+/// that are closer than the tolerance to the simplified line.
+/// A closed line (ring) is kept unchanged if its simplification would have
+/// fewer than 4 vertices.
+pub(crate) fn simplify_line(coords: &[Coord], tolerance: f64) -> Vec<Coord> {
+    let n = coords.len();
+    if n < 3 {
+        return coords.to_vec();
+    }
+    let tolerance_sq = tolerance * tolerance;
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    keep[n - 1] = true;
+    let mut stack = vec![(0, n - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (mut max_dist, mut index) = (0., first);
+        for (k, p) in coords.iter().enumerate().take(last).skip(first + 1) {
+            let d = segment_distance_sq(p, &coords[first], &coords[last]);
+            if d > max_dist {
+                max_dist = d;
+                index = k;
+            }
+        }
+        if max_dist > tolerance_sq {
+            keep[index] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+    let result = coords
+        .iter()
+        .zip(keep)
+        .filter(|(_, k)| *k)
+        .map(|(p, _)| *p)
+        .collect::<Vec<_>>();
+    if coords[0] == coords[n - 1] && result.len() < 4 {
+        coords.to_vec()
+    } else {
+        result
+    }
+}
+
+/// Squared distance between the point p and the segment [a, b].
+fn segment_distance_sq(p: &Coord, a: &Coord, b: &Coord) -> f64 {
+    let length_sq = distance_sq(a, b);
+    if length_sq == 0. {
+        return distance_sq(p, a);
+    }
+    let t = (((p.x - a.x) * (b.x - a.x) + (p.y - a.y) * (b.y - a.y)) / length_sq).clamp(0., 1.);
+    distance_sq(
+        p,
+        &Coord {
+            x: a.x + t * (b.x - a.x),
+            y: a.y + t * (b.y - a.y),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A deformation bending the horizontal lines
+    fn bend(p: &Coord) -> Coord {
+        Coord {
+            x: p.x,
+            y: p.y + p.x * p.x / 100.,
+        }
+    }
+
+    #[test]
+    fn test_densify() {
+        let line = [Coord { x: 0., y: 0. }, Coord { x: 100., y: 0. }];
+        assert_eq!(deform_line(&line, bend, None, 10.).len(), 2);
+        let dense = deform_line(&line, bend, Some(Densification::MaxSpacing(10.)), 10.);
+        assert_eq!(dense.len(), 11);
+        assert_eq!(dense[5], Coord { x: 50., y: 25. });
+        let dense = deform_line(
+            &line,
+            bend,
+            Some(Densification::ResolutionFraction(0.5)),
+            10.,
+        );
+        assert_eq!(dense.len(), 21);
+        // The number of inserted vertices is capped
+        let dense = deform_line(&line, bend, Some(Densification::MaxSpacing(1e-300)), 10.);
+        assert_eq!(dense.len(), MAX_INSERTED_VERTICES + 2);
+        assert!(!Densification::Adaptive {
+            tolerance: 1.,
+            max_depth: 64,
+        }
+        .is_valid());
+
+        let adaptive = deform_line(
+            &line,
+            bend,
+            Some(Densification::Adaptive {
+                tolerance: 1.,
+                max_depth: 10,
+            }),
+            10.,
+        );
+        // Each inserted middle deviates by (length / 2)² / 100, so the
+        // segments are split until they are shorter than 20
+        assert_eq!(adaptive.len(), 9);
+        for w in adaptive.windows(2) {
+            let m = bend(&Coord {
+                x: (w[0].x + w[1].x) / 2.,
+                y: 0.,
+            });
+            assert!((m.y - (w[0].y + w[1].y) / 2.).abs() <= 1.);
+        }
+    }
+
+    #[test]
+    fn test_simplify() {
+        let line = (0..=10)
+            .map(|k| Coord {
+                x: k as f64,
+                y: 0.2 * k.min(10 - k) as f64 + 0.01 * (k % 2) as f64,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            simplify_line(&line, 0.1),
+            vec![
+                Coord { x: 0., y: 0. },
+                Coord { x: 5., y: 1.01 },
+                Coord { x: 10., y: 0. }
+            ]
+        );
+        let ring = vec![
+            Coord { x: 0., y: 0. },
+            Coord { x: 1., y: 0. },
+            Coord { x: 1., y: 1. },
+            Coord { x: 0., y: 0. },
+        ];
+        assert_eq!(simplify_line(&ring, 10.), ring);
+    }
+}
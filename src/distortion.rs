@@ -0,0 +1,310 @@
+use crate::errors::Error;
+use crate::graticule::{check_spacing, multiples};
+use crate::grid::{Grid, GridType};
+use geo_types::{Coord, Polygon};
+
+/// The maximum number of segments of the polygons approximating the Tissot ellipses.
+const MAX_ELLIPSE_SEGMENTS: usize = 10_000;
+
+/// The local distortion of the deformation, derived from its Jacobian matrix
+/// (as described by Tissot's indicatrix: a small circle of the source space
+/// is deformed into an ellipse whose semi-axes are the maximum and the minimum scales).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distortion {
+    /// The area scale factor (determinant of the Jacobian matrix,
+    /// negative if the deformation folds the grid).
+    pub area_scale: f64,
+    /// The maximum scale factor (semi-major axis of the indicatrix).
+    pub max_scale: f64,
+    /// The minimum scale factor (semi-minor axis of the indicatrix).
+    pub min_scale: f64,
+    /// The maximum angular distortion, in degrees (0 for a conformal deformation).
+    pub angular_distortion: f64,
+    /// The direction (in degrees, counterclockwise from the x axis, between 0 and 180)
+    /// of the source space that is the most stretched.
+    pub principal_direction: f64,
+    /// The direction (in degrees, counterclockwise from the x axis, between 0 and 180)
+    /// of the semi-major axis of the indicatrix in the cartogram space.
+    pub principal_direction_image: f64,
+}
+
+impl Distortion {
+    /// Compute the distortion from the Jacobian matrix
+    /// (`[[dX/dx, dX/dy], [dY/dx, dY/dy]]`).
+    pub(crate) fn from_jacobian(jacobian: [[f64; 2]; 2]) -> Distortion {
+        let [[a, b], [c, d]] = jacobian;
+        // Eigen decomposition of the (symmetric) matrix J'J,
+        // whose eigenvalues are the squares of the scale factors
+        let p = a * a + c * c;
+        let q = a * b + c * d;
+        let r = b * b + d * d;
+        let mean = (p + r) / 2.;
+        let radius = (((p - r) / 2.).powi(2) + q * q).sqrt();
+        let max_scale = (mean + radius).sqrt();
+        let min_scale = (mean - radius).max(0.).sqrt();
+        let direction = 0.5 * (2. * q).atan2(p - r);
+        let image_direction = (c * direction.cos() + d * direction.sin())
+            .atan2(a * direction.cos() + b * direction.sin());
+        let angular_distortion = if max_scale + min_scale > 0. {
+            2. * ((max_scale - min_scale) / (max_scale + min_scale)).asin()
+        } else {
+            0.
+        };
+        Distortion {
+            area_scale: a * d - b * c,
+            max_scale,
+            min_scale,
+            angular_distortion: angular_distortion.to_degrees(),
+            principal_direction: direction.to_degrees().rem_euclid(180.),
+            principal_direction_image: image_direction.to_degrees().rem_euclid(180.),
+        }
+    }
+}
+
+/// The distortion of a cell of the grid (see [`Grid::cell_distortions`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellDistortion {
+    /// The line of the cell in the grid (from top to bottom).
+    pub i: usize,
+    /// The column of the cell in the grid (from left to right).
+    pub j: usize,
+    /// The distortion at the center of the cell.
+    pub distortion: Distortion,
+}
+
+impl Grid {
+    /// Compute the distortion (area scale factor, maximum and minimum scales, angular
+    /// distortion and principal directions) at the center of each cell of the grid,
+    /// from the Jacobian matrix of the bilinear interpolation of the cell.
+    ///
+    /// The cells are returned line by line (from top to bottom, and from left
+    /// to right in each line), as in [`get_grid`](Grid::get_grid), and their
+    /// position (i, j) in the grid is given.
+    pub fn cell_distortions(&self) -> Vec<CellDistortion> {
+        let (width, height) = self.grid_dimensions();
+        (0..(height - 1))
+            .flat_map(|i| (0..(width - 1)).map(move |j| (i, j)))
+            .map(|(i, j)| CellDistortion {
+                i,
+                j,
+                distortion: Distortion::from_jacobian(self.cell_jacobian(i, j, 0.5, 0.5)),
+            })
+            .collect()
+    }
+
+    /// Compute the distortion of the cell at the position (i, j) of the grid
+    /// (see [`cell_distortions`](Grid::cell_distortions)), if there is such a cell.
+    pub fn cell_distortion(&self, i: usize, j: usize) -> Option<CellDistortion> {
+        let (width, height) = self.grid_dimensions();
+        if i + 1 >= height || j + 1 >= width {
+            return None;
+        }
+        Some(CellDistortion {
+            i,
+            j,
+            distortion: Distortion::from_jacobian(self.cell_jacobian(i, j, 0.5, 0.5)),
+        })
+    }
+
+    /// Returns the polygons of the cells of the grid (either source grid or
+    /// interpolated grid, see [`get_grid`](Grid::get_grid)) along with their
+    /// distortion (see [`cell_distortions`](Grid::cell_distortions)),
+    /// for example to draw distortion maps.
+    pub fn cell_distortion_polygons(&self, grid_type: GridType) -> Vec<(Polygon, CellDistortion)> {
+        self.get_grid(grid_type)
+            .into_iter()
+            .zip(self.cell_distortions())
+            .collect()
+    }
+}
+
+/// A Tissot indicatrix: the ellipse obtained by deforming a small circle of the source
+/// space with the local linear approximation (the Jacobian matrix) of the deformation
+/// (see [`Grid::tissot_indicatrices`]).
+#[derive(Debug, Clone)]
+pub struct TissotEllipse {
+    /// The center of the circle, in the source space.
+    pub center: Coord,
+    /// The center of the ellipse, in the cartogram space.
+    pub center_image: Coord,
+    /// The ellipse, in the cartogram space.
+    pub polygon: Polygon,
+    /// The semi-major axis of the ellipse (in the unit of the coordinates).
+    pub semi_major_axis: f64,
+    /// The semi-minor axis of the ellipse (in the unit of the coordinates).
+    pub semi_minor_axis: f64,
+    /// The orientation of the semi-major axis (in degrees, counterclockwise
+    /// from the x axis, between 0 and 180).
+    pub orientation: f64,
+    /// The local distortion of the deformation at the center of the circle.
+    pub distortion: Distortion,
+}
+
+impl Grid {
+    /// Compute the Tissot indicatrices (see [`TissotEllipse`]) on a lattice of points
+    /// of the source space, at the multiples of the given spacing inside the grid
+    /// (at most 10 000 multiples along each axis),
+    /// for circles of the given radius (in the unit of the coordinates).
+    /// Each ellipse is approximated by a polygon with `n_segments` segments
+    /// (between 3 and 10 000).
+    pub fn tissot_indicatrices(
+        &self,
+        spacing: f64,
+        radius: f64,
+        n_segments: usize,
+    ) -> Result<Vec<TissotEllipse>, Error> {
+        check_spacing(spacing)?;
+        let bbox = self.bbox();
+        let xs = multiples(bbox.xmin, bbox.xmax, spacing)?.collect::<Vec<_>>();
+        let points = multiples(bbox.ymin, bbox.ymax, spacing)?
+            .flat_map(|y| xs.iter().map(move |&x| Coord { x, y }))
+            .collect::<Vec<_>>();
+        self.tissot_indicatrices_at(&points, radius, n_segments)
+    }
+
+    /// Compute the Tissot indicatrices (see [`TissotEllipse`]) at the given points
+    /// of the source space (which must be inside the grid), for circles of the given
+    /// radius (in the unit of the coordinates).
+    /// Each ellipse is approximated by a polygon with `n_segments` segments
+    /// (between 3 and 10 000).
+    pub fn tissot_indicatrices_at(
+        &self,
+        points: &[Coord],
+        radius: f64,
+        n_segments: usize,
+    ) -> Result<Vec<TissotEllipse>, Error> {
+        if !(radius.is_finite() && radius > 0.) {
+            return Err(Error::InvalidRadius(radius));
+        }
+        if !(3..=MAX_ELLIPSE_SEGMENTS).contains(&n_segments) {
+            return Err(Error::InvalidSegmentCount(n_segments));
+        }
+        let bbox = self.bbox();
+        if !points.iter().all(|p| bbox.contains(p)) {
+            return Err(Error::PointNotInBBox);
+        }
+        Ok(points
+            .iter()
+            .map(|center| {
+                let jacobian = self.jacobian_at(center);
+                let center_image = self.get_interp_point(center).unwrap();
+                let ring = (0..=n_segments)
+                    .map(|k| {
+                        let angle =
+                            (k % n_segments) as f64 / n_segments as f64 * std::f64::consts::TAU;
+                        let (dx, dy) = (radius * angle.cos(), radius * angle.sin());
+                        Coord {
+                            x: center_image.x + jacobian[0][0] * dx + jacobian[0][1] * dy,
+                            y: center_image.y + jacobian[1][0] * dx + jacobian[1][1] * dy,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let distortion = Distortion::from_jacobian(jacobian);
+                TissotEllipse {
+                    center: *center,
+                    center_image,
+                    polygon: Polygon::new(ring.into(), vec![]),
+                    semi_major_axis: radius * distortion.max_scale,
+                    semi_minor_axis: radius * distortion.min_scale,
+                    orientation: distortion.principal_direction_image,
+                    distortion,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::lattice;
+
+    #[test]
+    fn test_distortion_from_jacobian() {
+        // Scaling by 2 along the direction at 30°
+        let (cos, sin) = (30f64.to_radians().cos(), 30f64.to_radians().sin());
+        let jacobian = [[1. + cos * cos, cos * sin], [cos * sin, 1. + sin * sin]];
+        let d = Distortion::from_jacobian(jacobian);
+        assert!((d.area_scale - 2.).abs() < 1e-12);
+        assert!((d.max_scale - 2.).abs() < 1e-12);
+        assert!((d.min_scale - 1.).abs() < 1e-12);
+        assert!((d.principal_direction - 30.).abs() < 1e-9);
+        assert!((d.principal_direction_image - 30.).abs() < 1e-9);
+        assert!((d.angular_distortion - 2. * (1f64 / 3.).asin().to_degrees()).abs() < 1e-9);
+
+        // Rotation by 90° and uniform scaling: no angular distortion
+        let d = Distortion::from_jacobian([[0., -3.], [3., 0.]]);
+        assert!((d.area_scale - 9.).abs() < 1e-12);
+        assert!(d.angular_distortion.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cell_distortions() {
+        let source = lattice();
+        let image = source
+            .iter()
+            .map(|p| geo_types::Coord {
+                x: p.x * 1.2 + (p.y / 10.).powi(2),
+                y: p.y - p.x * 0.2,
+            })
+            .collect::<Vec<_>>();
+        let grid = Grid::builder(&source, &image).build().unwrap();
+        let (width, height) = grid.grid_dimensions();
+        let cells = grid.cell_distortions();
+        assert_eq!(cells.len(), (width - 1) * (height - 1));
+        assert_eq!(grid.cell_distortion(2, 1), Some(cells[2 * (width - 1) + 1]));
+        assert_eq!(grid.cell_distortion(height - 1, 0), None);
+        // The area of a deformed cell is the determinant of the Jacobian
+        // matrix at its center times the area of the cell (as the determinant
+        // of a bilinear interpolation varies linearly in the cell)
+        let (rx, ry) = grid.resolution_xy();
+        for (polygon, cell) in grid.cell_distortion_polygons(GridType::Interpolated) {
+            let ring = &polygon.exterior().0;
+            let area = ring
+                .windows(2)
+                .map(|w| w[0].x * w[1].y - w[1].x * w[0].y)
+                .sum::<f64>()
+                / 2.;
+            assert!((area.abs() - cell.distortion.area_scale.abs() * rx * ry).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_tissot_indicatrices() {
+        let source = lattice();
+        let image = source
+            .iter()
+            .map(|p| Coord {
+                x: p.x * 1.5,
+                y: p.y + (p.x / 10.).powi(2),
+            })
+            .collect::<Vec<_>>();
+        let grid = Grid::builder(&source, &image).build().unwrap();
+        let ellipses = grid.tissot_indicatrices(10., 2., 36).unwrap();
+        assert!(!ellipses.is_empty());
+        for e in ellipses.iter() {
+            assert_eq!(e.polygon.exterior().0.len(), 37);
+            assert!(e.semi_major_axis >= e.semi_minor_axis);
+            // The vertices of the ellipse are between its semi-axes from its center
+            for p in e.polygon.exterior().0.iter() {
+                let d =
+                    ((p.x - e.center_image.x).powi(2) + (p.y - e.center_image.y).powi(2)).sqrt();
+                assert!(d <= e.semi_major_axis + 1e-9 && d >= e.semi_minor_axis - 1e-9);
+            }
+        }
+        assert!(matches!(
+            grid.tissot_indicatrices_at(&[Coord { x: 1e6, y: 0. }], 2., 36),
+            Err(Error::PointNotInBBox)
+        ));
+        assert!(matches!(
+            grid.tissot_indicatrices(1e-12, 2., 36),
+            Err(Error::TooManyGraticuleLines(..))
+        ));
+        for n_segments in [0, 2, MAX_ELLIPSE_SEGMENTS + 1] {
+            assert!(matches!(
+                grid.tissot_indicatrices(10., 2., n_segments),
+                Err(Error::InvalidSegmentCount(n)) if n == n_segments
+            ));
+        }
+    }
+}
@@ -1,3 +1,9 @@
+use crate::densify::Densification;
+#[cfg(feature = "moving-points-unipolar")]
+use crate::moving_points_unipolar::{
+    DurationMapping, OriginsRule, ReferenceSpeedModel, RingResolution,
+};
+use crate::validation::ValidationReport;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,6 +17,73 @@ pub enum Error {
     #[error("The provided point don't fall inside the bounding box of the grid")]
     PointNotInBBox,
 
+    #[error("The provided point don't fall inside the interpolated grid")]
+    PointNotInInterpolatedGrid,
+
+    #[error("The homologous points are invalid: {0}")]
+    InvalidPoints(ValidationReport),
+
+    #[error(
+        "The bounding box has non-finite coordinates or its minimum is greater than its maximum"
+    )]
+    InvalidBBox,
+
+    #[error("The zone covered by the grid has a zero width or height")]
+    DegenerateBBox,
+
+    #[error("Invalid precision {0} (it must be a finite positive value)")]
+    InvalidPrecision(f64),
+
+    #[error("Invalid resolution {0} (it must be a finite positive value)")]
+    InvalidResolution(f64),
+
+    #[error("Invalid cell count {0} (it must be positive)")]
+    InvalidCellCount(usize),
+
+    #[error("The grid would have {0} nodes, more than the maximum of {1} (use larger cells)")]
+    TooManyNodes(usize, usize),
+
+    #[error("Invalid padding {0} (it must be a finite value, positive or zero)")]
+    InvalidPadding(f64),
+
+    #[error("Invalid tolerance {0} (it must be a finite value, positive or zero)")]
+    InvalidTolerance(f64),
+
+    #[error("Invalid densification {0:?} (the spacing and the tolerance must be finite positive values and the maximum depth at most 16)")]
+    InvalidDensification(Densification),
+
+    #[error("Invalid spacing {0} (it must be a finite positive value)")]
+    InvalidSpacing(f64),
+
+    #[error(
+        "The spacing {0} would give {1} lines, more than the maximum of {2} (use a larger spacing)"
+    )]
+    TooManyGraticuleLines(f64, usize, usize),
+
+    #[error("Invalid radius {0} (it must be a finite positive value)")]
+    InvalidRadius(f64),
+
+    #[error("Invalid number of segments {0} (it must be between 3 and 10000)")]
+    InvalidSegmentCount(usize),
+
+    #[error("Invalid morphing factor {0} (it must be between 0 and 1)")]
+    InvalidMorphingFactor(f64),
+
+    #[error("Invalid number of frames {0} (at least 2 frames are needed)")]
+    InvalidFrameCount(usize),
+
+    #[error("The raster is invalid (it has no band, its dimensions don't match the size of its buffer or its geotransform is not invertible)")]
+    InvalidRaster,
+
+    #[error("Invalid band {0} (it must be lower than the number of bands of the raster)")]
+    InvalidBand(usize),
+
+    #[error("The raster is rotated, which is not supported by this format")]
+    RotatedRaster,
+
+    #[error("Unable to write the raster: {0}")]
+    RasterWrite(String),
+
     #[error("The two sets of input points for Procrustes analysis must have the same length")]
     ProcrustesInputLengthMismatch,
 
@@ -22,11 +95,145 @@ pub enum Error {
     #[error("No reference point found")]
     NoReferencePoint,
 
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error("More than one reference point found (durations of 0 at index {0} and {1})")]
+    MultipleReferencePoints(usize, usize),
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error("Invalid duration at index {0} (durations must not be NaN or negative)")]
+    InvalidDuration(usize),
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error("No origin provided")]
+    NoOrigin,
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error(
+        "Invalid duration from the origin {0} at index {1} (durations must not be NaN or negative)"
+    )]
+    InvalidOriginDuration(usize, usize),
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error("Invalid origins rule {0:?} (the power must be a finite value, positive or zero)")]
+    InvalidOriginsRule(OriginsRule),
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error(
+        "No point can be reached (with a non-zero finite duration) to compute the reference speed"
+    )]
+    NoReachablePoint,
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error("Invalid reference speed model {0:?} (the number of sectors and the bandwidth must be positive)")]
+    InvalidReferenceSpeedModel(ReferenceSpeedModel),
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error("Invalid duration mapping {0:?} (the pivot and the exponent must be positive, the breakpoints sorted by increasing duration and value, and the mapped durations finite, non-negative and positive for positive durations)")]
+    InvalidDurationMapping(DurationMapping),
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error("Invalid ring resolution {0:?} (at least 3 segments or a positive maximum chord error are required)")]
+    InvalidRingResolution(RingResolution),
+
+    #[cfg(feature = "moving-points-unipolar")]
+    #[error("Invalid ring step {0} (the steps must be positive and finite)")]
+    InvalidRingStep(f64),
+
     #[cfg(feature = "moving-points-multipolar")]
     #[error("The duration matrix is not square")]
     DurationMatrixNotSquare,
 
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The duration matrix has a non-zero value on its diagonal (at index {0})")]
+    DurationMatrixNonZeroDiagonal(usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The duration matrix contains a negative duration (at row {0} and column {1})")]
+    NegativeDuration(usize, usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The duration matrix contains a missing duration (at row {0} and column {1})")]
+    MissingDuration(usize, usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The missing duration between the points {0} and {1} can't be imputed as they are not connected by known durations")]
+    DisconnectedDurationMatrix(usize, usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The landmarks must be at least 3 distinct points, each with its durations to all the points")]
+    InvalidLandmarks,
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("Unable to read the CSV file: {0}")]
+    CsvRead(#[from] csv::Error),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error(
+        "Missing fields in the CSV file (at line {0}: {1} fields found, at least {2} expected)"
+    )]
+    CsvMissingFields(u64, usize, usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The row {0:?} of the header is missing from the CSV file")]
+    CsvMissingRow(String),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("Invalid value {2:?} in the CSV file (at line {0} and column {1})")]
+    CsvInvalidValue(u64, usize, String),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The row name {1:?} doesn't match the header of the CSV file (at line {0})")]
+    CsvRowNameMismatch(u64, String),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The label {0:?} is duplicated")]
+    DuplicateLabel(String),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The identifier {0:?} doesn't match any label of the duration matrix")]
+    UnmatchedLabel(String),
+
     #[cfg(feature = "moving-points-multipolar")]
     #[error("An error occurred during the PCoA analysis")]
     PCoAUnsuccessful,
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("Invalid pair of axes ({0}, {1}): the axes must be distinct and lower than the number of computed axes")]
+    InvalidAxes(usize, usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The number of points in the initial configuration and the size of the duration matrix must be equal")]
+    InvalidInitialConfigurationLength,
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The initial configuration must have finite coordinates and not all its points can coincide")]
+    InvalidInitialConfiguration,
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("Invalid anchor weight {0} (it must be a finite value, positive or zero)")]
+    InvalidAnchorWeight(f64),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("Invalid maximum number of iterations {0} (it must be positive)")]
+    InvalidMaxIterations(usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The number of positions and the size of the duration matrix must be equal")]
+    InvalidPositionsLength,
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The number of source points and the size of the duration matrix must be equal")]
+    InvalidSourcePointsLength,
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The duration matrix must have at least 2 rows and columns (got {0})")]
+    DurationMatrixTooSmall(usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The duration matrix is not symmetric (at row {0} and column {1})")]
+    AsymmetricDurationMatrix(usize, usize),
+
+    #[cfg(feature = "moving-points-multipolar")]
+    #[error("The positions or the durations all coincide, the goodness-of-fit can't be computed")]
+    DegenerateConfiguration,
 }
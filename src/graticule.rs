@@ -0,0 +1,240 @@
+use crate::bbox::BBox;
+use crate::densify::Densification;
+use crate::errors::Error;
+use crate::grid::Grid;
+use geo_types::{Coord, LineString, MultiLineString};
+
+/// The coordinate that is constant along a graticule line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraticuleAxis {
+    /// A line of constant x (or a meridian, of constant longitude).
+    X,
+    /// A line of constant y (or a parallel, of constant latitude).
+    Y,
+}
+
+/// A graticule line, deformed through the grid
+/// (see [`Grid::graticule`] and [`Grid::graticule_lonlat`]).
+#[derive(Debug, Clone)]
+pub struct GraticuleLine {
+    /// The deformed line (which can have several parts if the line
+    /// leaves the grid and enters it again).
+    pub geometry: MultiLineString,
+    /// The coordinate that is constant along the line.
+    pub axis: GraticuleAxis,
+    /// The value of this coordinate.
+    pub value: f64,
+    /// The label of the line (such as `"650000"` for a projected graticule
+    /// or `"45°N"` for a parallel).
+    pub label: String,
+}
+
+impl Grid {
+    /// Generate a graticule in the CRS of the grid, with lines of constant x every
+    /// `spacing_x` and lines of constant y every `spacing_y` (the lines being aligned
+    /// on the multiples of the spacings), covering the grid and deformed through it.
+    /// There can be at most 10 000 lines along each axis.
+    ///
+    /// The lines are densified (see [`Densification`]) before their deformation,
+    /// so that they follow the deformation of the grid.
+    pub fn graticule(
+        &self,
+        spacing_x: f64,
+        spacing_y: f64,
+        densification: Densification,
+    ) -> Result<Vec<GraticuleLine>, Error> {
+        check_spacing(spacing_x)?;
+        check_spacing(spacing_y)?;
+        let bbox = self.bbox();
+        let mut lines = Vec::new();
+        for x in multiples(bbox.xmin, bbox.xmax, spacing_x)? {
+            let line = vec![Coord { x, y: bbox.ymin }, Coord { x, y: bbox.ymax }];
+            lines.push((GraticuleAxis::X, x, format_value(x), vec![line]));
+        }
+        for y in multiples(bbox.ymin, bbox.ymax, spacing_y)? {
+            let line = vec![Coord { x: bbox.xmin, y }, Coord { x: bbox.xmax, y }];
+            lines.push((GraticuleAxis::Y, y, format_value(y), vec![line]));
+        }
+        self.deform_graticule(lines, Some(densification))
+    }
+
+    /// Generate a graticule of meridians every `spacing_lon` degrees and of parallels
+    /// every `spacing_lat` degrees, within the given extent (in longitude / latitude),
+    /// deformed through the grid (with at most 10 000 meridians and 10 000 parallels).
+    ///
+    /// As this crate doesn't handle projections, the `project` function has to
+    /// project a (longitude, latitude) coordinate to the CRS of the grid.
+    /// The meridians and parallels are sampled every `step` degrees before
+    /// being projected, and only their parts inside the grid are kept.
+    pub fn graticule_lonlat<F>(
+        &self,
+        extent: BBox,
+        spacing_lon: f64,
+        spacing_lat: f64,
+        step: f64,
+        project: F,
+    ) -> Result<Vec<GraticuleLine>, Error>
+    where
+        F: Fn(&Coord) -> Coord,
+    {
+        check_spacing(spacing_lon)?;
+        check_spacing(spacing_lat)?;
+        check_spacing(step)?;
+        let bbox = self.bbox();
+        let sample = |from: f64, to: f64, point: &dyn Fn(f64) -> Coord| {
+            let n = ((to - from) / step).ceil().max(1.) as usize;
+            let projected = (0..=n)
+                .map(|k| project(&point(from + (to - from) * k as f64 / n as f64)))
+                .collect::<Vec<_>>();
+            // Keep the runs of consecutive points inside the grid
+            projected
+                .split(|p| !bbox.contains(p))
+                .filter(|part| part.len() > 1)
+                .map(|part| part.to_vec())
+                .collect::<Vec<_>>()
+        };
+        let mut lines = Vec::new();
+        for lon in multiples(extent.xmin, extent.xmax, spacing_lon)? {
+            let parts = sample(extent.ymin, extent.ymax, &|lat| Coord { x: lon, y: lat });
+            lines.push((GraticuleAxis::X, lon, format_degrees(lon, "E", "W"), parts));
+        }
+        for lat in multiples(extent.ymin, extent.ymax, spacing_lat)? {
+            let parts = sample(extent.xmin, extent.xmax, &|lon| Coord { x: lon, y: lat });
+            lines.push((GraticuleAxis::Y, lat, format_degrees(lat, "N", "S"), parts));
+        }
+        self.deform_graticule(lines, None)
+    }
+
+    /// Deform the parts of the graticule lines, dropping the lines without any part.
+    fn deform_graticule(
+        &self,
+        lines: Vec<(GraticuleAxis, f64, String, Vec<Vec<Coord>>)>,
+        densification: Option<Densification>,
+    ) -> Result<Vec<GraticuleLine>, Error> {
+        lines
+            .into_iter()
+            .filter(|(_, _, _, parts)| !parts.is_empty())
+            .map(|(axis, value, label, parts)| {
+                let geometry = geo_types::Geometry::MultiLineString(MultiLineString(
+                    parts.into_iter().map(LineString).collect(),
+                ));
+                let geometry = match self
+                    .interpolate_layer_densified(&[geometry], densification, None)?
+                    .pop()
+                {
+                    Some(geo_types::Geometry::MultiLineString(mls)) => mls,
+                    _ => unreachable!(),
+                };
+                Ok(GraticuleLine {
+                    geometry,
+                    axis,
+                    value,
+                    label,
+                })
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn check_spacing(spacing: f64) -> Result<(), Error> {
+    if spacing.is_finite() && spacing > 0. {
+        Ok(())
+    } else {
+        Err(Error::InvalidSpacing(spacing))
+    }
+}
+
+/// The maximum number of multiples of a spacing (and thus of graticule lines
+/// along an axis).
+pub(crate) const MAX_GRATICULE_LINES: usize = 10_000;
+
+/// The multiples of the spacing between min and max (inclusive),
+/// failing if there are more than [`MAX_GRATICULE_LINES`] of them.
+pub(crate) fn multiples(
+    min: f64,
+    max: f64,
+    spacing: f64,
+) -> Result<impl Iterator<Item = f64>, Error> {
+    let first = (min / spacing).ceil();
+    let last = (max / spacing).floor();
+    let count = (last - first + 1.).max(0.);
+    if count > MAX_GRATICULE_LINES as f64 {
+        return Err(Error::TooManyGraticuleLines(
+            spacing,
+            count as usize,
+            MAX_GRATICULE_LINES,
+        ));
+    }
+    let (first, last) = (first as i64, last as i64);
+    Ok((first..=last).map(move |k| round(k as f64 * spacing)))
+}
+
+/// Round a value to remove the floating point noise of the multiples
+/// (such as 0.30000000000000004 for 3 * 0.1).
+fn round(value: f64) -> f64 {
+    (value * 1e9).round() / 1e9
+}
+
+fn format_value(value: f64) -> String {
+    format!("{}", value)
+}
+
+fn format_degrees(value: f64, positive: &str, negative: &str) -> String {
+    if value > 0. {
+        format!("{}°{}", value, positive)
+    } else if value < 0. {
+        format!("{}°{}", -value, negative)
+    } else {
+        "0°".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sheared_grid;
+
+    #[test]
+    fn test_graticule() {
+        let grid = sheared_grid();
+        let bbox = grid.bbox();
+        let lines = grid
+            .graticule(10., 20., Densification::ResolutionFraction(0.5))
+            .unwrap();
+        let n_x = lines.iter().filter(|l| l.axis == GraticuleAxis::X).count();
+        let n_y = lines.len() - n_x;
+        assert_eq!(n_x, multiples(bbox.xmin, bbox.xmax, 10.).unwrap().count());
+        assert_eq!(n_y, multiples(bbox.ymin, bbox.ymax, 20.).unwrap().count());
+        let line = lines.iter().find(|l| l.label == "20").unwrap();
+        assert_eq!(line.axis, GraticuleAxis::X);
+        // The line is densified (every 2.5 units) and crosses the whole grid
+        assert_eq!(line.geometry.0.len(), 1);
+        assert!(line.geometry.0[0].0.len() > ((bbox.ymax - bbox.ymin) / 2.5) as usize);
+        assert!(matches!(
+            grid.graticule(0., 10., Densification::MaxSpacing(1.)),
+            Err(Error::InvalidSpacing(_))
+        ));
+        assert!(matches!(
+            grid.graticule(1e-12, 10., Densification::MaxSpacing(1.)),
+            Err(Error::TooManyGraticuleLines(_, _, MAX_GRATICULE_LINES))
+        ));
+    }
+
+    #[test]
+    fn test_graticule_lonlat() {
+        let grid = sheared_grid();
+        // A (fake) projection of the longitudes and latitudes to the grid CRS
+        let project = |c: &Coord| Coord {
+            x: c.x * 10.,
+            y: c.y * 10.,
+        };
+        let lines = grid
+            .graticule_lonlat(BBox::new(-10., -10., 10., 10.), 1., 2.5, 0.1, project)
+            .unwrap();
+        let labels = lines.iter().map(|l| l.label.as_str()).collect::<Vec<_>>();
+        assert!(labels.contains(&"0°"));
+        assert!(labels.contains(&"3°E"));
+        assert!(labels.contains(&"2.5°N"));
+        assert!(!labels.contains(&"1°W"));
+    }
+}
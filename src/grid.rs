@@ -1,14 +1,22 @@
 use crate::bbox::BBox;
+use crate::densify::{deform_line, simplify_line, Densification};
 use crate::errors::Error;
-use crate::node::NodeSet;
+use crate::grid_builder::GridBuilder;
+use crate::morphing::check_morphing_factor;
+use crate::node::{CellSize, NodeSet};
 use crate::rectangle::Rectangle2D;
 use crate::utils;
 use crate::utils::distance_sq;
+use crate::validation::validate_points;
 use geo_types::Coord;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use std::fmt::Debug;
 
+/// The default convergence tolerance of the smoothing of the grid
+/// (see [`GridBuilder::tolerance`]).
+pub(crate) const DEFAULT_TOLERANCE: f64 = 0.0001;
+
 /// The type of grid to retrieve (source or interpolated,
 /// see [`Grid::get_grid`](Grid::get_grid) method).
 #[derive(Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -26,6 +34,28 @@ pub struct RMSE {
     pub rmse_y: f64,
 }
 
+/// An index of the cells of the transformed grid (see [`Grid::cell_index`]):
+/// the extent of the transformed grid is divided into buckets, each one
+/// listing the cells whose bounding box intersects it.
+pub(crate) struct CellIndex {
+    min: Coord,
+    size_x: f64,
+    size_y: f64,
+    n_x: usize,
+    n_y: usize,
+    buckets: Vec<Vec<(usize, usize)>>,
+}
+
+impl CellIndex {
+    /// The position (x, y) of the bucket containing the point
+    /// (clamped to the buckets of the index).
+    fn bucket(&self, p: &Coord) -> (usize, usize) {
+        let x = ((p.x - self.min.x) / self.size_x).floor().max(0.) as usize;
+        let y = ((p.y - self.min.y) / self.size_y).floor().max(0.) as usize;
+        (x.min(self.n_x - 1), y.min(self.n_y - 1))
+    }
+}
+
 /// The grid for interpolating and deforming geometries.
 /// Based on Waldo Tobler bidimensional regression.
 ///
@@ -76,7 +106,11 @@ impl Grid {
     ///
     /// Note that the number of source points must be equal to the number of
     /// image points, and they must be given in the same order (as they are
-    /// homologous points).
+    /// homologous points). The points are checked using [`validate_points`](crate::validate_points)
+    /// and an error is returned if they contain non-finite coordinates,
+    /// have an extent of zero width or height, or are too few.
+    /// An error is also returned if the precision isn't a finite positive value
+    /// or if it would create a grid of more than 10 million nodes.
     pub fn new(
         source_points: &[Coord],
         image_points: &[Coord],
@@ -87,8 +121,39 @@ impl Grid {
         if (source_points.len() != image_points.len()) || source_points.is_empty() {
             return Err(Error::InvalidInputPointsLength);
         }
-        let mut nodes = NodeSet::new(source_points, precision, bbox);
+        let report = validate_points(source_points, image_points);
+        if report.has_errors() {
+            return Err(Error::InvalidPoints(report));
+        }
+        if !(precision.is_finite() && precision > 0.) {
+            return Err(Error::InvalidPrecision(precision));
+        }
+        let nodes = NodeSet::new(source_points, CellSize::Precision(precision), bbox, 0.)?;
+        Ok(Grid::from_nodes(
+            nodes,
+            source_points,
+            image_points,
+            n_iter,
+            DEFAULT_TOLERANCE,
+        ))
+    }
+
+    /// Create a [`GridBuilder`] to configure the creation of a grid
+    /// (resolution, number of iterations, bounding box, padding, tolerance)
+    /// from the given source and image points.
+    pub fn builder<'a>(source_points: &'a [Coord], image_points: &'a [Coord]) -> GridBuilder<'a> {
+        GridBuilder::new(source_points, image_points)
+    }
 
+    /// Create the grid from its nodes and adjust them using
+    /// the source and image points.
+    pub(crate) fn from_nodes(
+        mut nodes: NodeSet,
+        source_points: &[Coord],
+        image_points: &[Coord],
+        n_iter: usize,
+        tolerance: f64,
+    ) -> Grid {
         for p in source_points {
             nodes.increase_weight_adjacent_nodes(p);
         }
@@ -109,8 +174,8 @@ impl Grid {
             },
             r_squared: 0.0,
         };
-        g.interpolate(source_points, image_points, n_iter);
-        Ok(g)
+        g.interpolate(source_points, image_points, n_iter, tolerance);
+        g
     }
 
     /// Interpolate on the grid the local transformations between
@@ -118,7 +183,13 @@ impl Grid {
     /// This method performs bidimensional regression by iteratively
     /// adjusting a grid of nodes to minimize the differences between
     /// the source and image points.
-    fn interpolate(&mut self, points: &[Coord], image_points: &[Coord], n_iter: usize) {
+    fn interpolate(
+        &mut self,
+        points: &[Coord],
+        image_points: &[Coord],
+        n_iter: usize,
+        tolerance: f64,
+    ) {
         // let rect = Rectangle2D::from_points(points);
         // let rect_adj = Rectangle2D::from_points(image_points);
         let mut rect = Rectangle2D::new(0., 0., -1., -1.);
@@ -134,8 +205,9 @@ impl Grid {
         let scale_x = rect_adj.width() / rect.width();
         let scale_y = rect_adj.height() / rect.height();
 
-        let resolution = self.nodes.resolution;
-        let square_resolution = resolution * resolution;
+        let resolution_x = self.nodes.resolution_x;
+        let resolution_y = self.nodes.resolution_y;
+        let cell_area = resolution_x * resolution_y;
         let width = self.nodes.width;
         let height = self.nodes.height;
         let rect_dim = self.nodes.zone.width() * self.nodes.zone.height();
@@ -155,9 +227,9 @@ impl Grid {
                 ];
 
                 let ux1 = src_pt.x - adj_nodes[0].source.x;
-                let ux2 = resolution - ux1;
+                let ux2 = resolution_x - ux1;
                 let vy1 = src_pt.y - adj_nodes[2].source.y;
-                let vy2 = resolution - vy1;
+                let vy2 = resolution_y - vy1;
                 let u = 1. / (ux1 * ux1 + ux2 * ux2);
                 let v = 1. / (vy1 * vy1 + vy2 * vy2);
                 let w = [vy1 * ux2, vy1 * ux1, vy2 * ux2, vy2 * ux1];
@@ -177,24 +249,24 @@ impl Grid {
                 }
 
                 // Compute the local transformation using bilinear interpolation
-                let hx1 = ux1 / resolution * (adj_nodes[1].interp.x - adj_nodes[0].interp.x)
+                let hx1 = ux1 / resolution_x * (adj_nodes[1].interp.x - adj_nodes[0].interp.x)
                     + adj_nodes[0].interp.x;
-                let hx2 = ux1 / resolution * (adj_nodes[3].interp.x - adj_nodes[2].interp.x)
+                let hx2 = ux1 / resolution_x * (adj_nodes[3].interp.x - adj_nodes[2].interp.x)
                     + adj_nodes[2].interp.x;
 
-                let hx = vy1 / resolution * (hx1 - hx2) + hx2;
+                let hx = vy1 / resolution_y * (hx1 - hx2) + hx2;
 
-                let hy1 = ux1 / resolution * (adj_nodes[1].interp.y - adj_nodes[0].interp.y)
+                let hy1 = ux1 / resolution_x * (adj_nodes[1].interp.y - adj_nodes[0].interp.y)
                     + adj_nodes[0].interp.y;
-                let hy2 = ux1 / resolution * (adj_nodes[3].interp.y - adj_nodes[2].interp.y)
+                let hy2 = ux1 / resolution_x * (adj_nodes[3].interp.y - adj_nodes[2].interp.y)
                     + adj_nodes[2].interp.y;
 
-                let hy = vy1 / resolution * (hy1 - hy2) + hy2;
+                let hy = vy1 / resolution_y * (hy1 - hy2) + hy2;
 
                 let delta_x = adj_pt.x - hx;
                 let delta_y = adj_pt.y - hy;
-                let dx = delta_x * square_resolution;
-                let dy = delta_y * square_resolution;
+                let dx = delta_x * cell_area;
+                let dy = delta_y * cell_area;
 
                 for i in 0..4 {
                     let adj_x =
@@ -228,7 +300,7 @@ impl Grid {
                         }
                     }
                 }
-                if l > 5 && delta.sqrt() < 0.0001 {
+                if l > 5 && delta.sqrt() < tolerance {
                     break;
                 }
             }
@@ -253,31 +325,257 @@ impl Grid {
 
     fn _get_interp_point(&self, src_point: &Coord) -> Coord {
         let adj_nodes = self.nodes.get_adjacent_nodes_ref(src_point);
-        let resolution = self.nodes.resolution;
+        let resolution_x = self.nodes.resolution_x;
+        let resolution_y = self.nodes.resolution_y;
         let ux1 = src_point.x - adj_nodes[0].source.x;
         let vy1 = src_point.y - adj_nodes[2].source.y;
-        let hx1 = ux1 / resolution * (adj_nodes[1].interp.x - adj_nodes[0].interp.x)
+        let hx1 = ux1 / resolution_x * (adj_nodes[1].interp.x - adj_nodes[0].interp.x)
             + adj_nodes[0].interp.x;
-        let hx2 = ux1 / resolution * (adj_nodes[3].interp.x - adj_nodes[2].interp.x)
+        let hx2 = ux1 / resolution_x * (adj_nodes[3].interp.x - adj_nodes[2].interp.x)
             + adj_nodes[2].interp.x;
-        let hx = vy1 / resolution * (hx1 - hx2) + hx2;
-        let hy1 = ux1 / resolution * (adj_nodes[1].interp.y - adj_nodes[0].interp.y)
+        let hx = vy1 / resolution_y * (hx1 - hx2) + hx2;
+        let hy1 = ux1 / resolution_x * (adj_nodes[1].interp.y - adj_nodes[0].interp.y)
             + adj_nodes[0].interp.y;
-        let hy2 = ux1 / resolution * (adj_nodes[3].interp.y - adj_nodes[2].interp.y)
+        let hy2 = ux1 / resolution_x * (adj_nodes[3].interp.y - adj_nodes[2].interp.y)
             + adj_nodes[2].interp.y;
-        let hy = vy1 / resolution * (hy1 - hy2) + hy2;
+        let hy = vy1 / resolution_y * (hy1 - hy2) + hy2;
 
         Coord { x: hx, y: hy }
     }
 
+    /// Find the point of the source grid that is interpolated to the
+    /// given point of the transformed grid (i.e. apply the inverse of the
+    /// deformation).
+    /// This is useful for drawing geometries created in the cartogram space
+    /// (such as isochrones) on the undeformed map.
+    pub fn get_inverse_interp_point(&self, interp_point: &Coord) -> Result<Coord, Error> {
+        self._get_inverse_interp_point(interp_point)
+            .ok_or(Error::PointNotInInterpolatedGrid)
+    }
+
+    fn _get_inverse_interp_point(&self, interp_point: &Coord) -> Option<Coord> {
+        for i in 0..(self.nodes.height - 1) {
+            for j in 0..(self.nodes.width - 1) {
+                if let Some(p) = self.invert_in_cell(i, j, interp_point) {
+                    return Some(p);
+                }
+            }
+        }
+        None
+    }
+
+    /// Build an index of the cells of the transformed grid, to find the source point
+    /// of many points faster than with [`get_inverse_interp_point`](Grid::get_inverse_interp_point).
+    pub(crate) fn cell_index(&self) -> CellIndex {
+        let (n_x, n_y) = (self.nodes.width - 1, self.nodes.height - 1);
+        let bounds = |i: usize, j: usize| {
+            let n = [
+                self.nodes.get_node(i, j).interp,
+                self.nodes.get_node(i, j + 1).interp,
+                self.nodes.get_node(i + 1, j).interp,
+                self.nodes.get_node(i + 1, j + 1).interp,
+            ];
+            n.iter().skip(1).fold((n[0], n[0]), |(min, max), p| {
+                (
+                    Coord {
+                        x: min.x.min(p.x),
+                        y: min.y.min(p.y),
+                    },
+                    Coord {
+                        x: max.x.max(p.x),
+                        y: max.y.max(p.y),
+                    },
+                )
+            })
+        };
+        let mut min = Coord {
+            x: f64::INFINITY,
+            y: f64::INFINITY,
+        };
+        let mut max = Coord {
+            x: f64::NEG_INFINITY,
+            y: f64::NEG_INFINITY,
+        };
+        for i in 0..n_y {
+            for j in 0..n_x {
+                let (cell_min, cell_max) = bounds(i, j);
+                min = Coord {
+                    x: min.x.min(cell_min.x),
+                    y: min.y.min(cell_min.y),
+                };
+                max = Coord {
+                    x: max.x.max(cell_max.x),
+                    y: max.y.max(cell_max.y),
+                };
+            }
+        }
+        // Use as many buckets as cells
+        let mut index = CellIndex {
+            min,
+            size_x: (max.x - min.x) / n_x as f64,
+            size_y: (max.y - min.y) / n_y as f64,
+            n_x,
+            n_y,
+            buckets: vec![Vec::new(); n_x * n_y],
+        };
+        for i in 0..n_y {
+            for j in 0..n_x {
+                let (cell_min, cell_max) = bounds(i, j);
+                let (x0, y0) = index.bucket(&cell_min);
+                let (x1, y1) = index.bucket(&cell_max);
+                for y in y0..=y1 {
+                    for x in x0..=x1 {
+                        index.buckets[y * n_x + x].push((i, j));
+                    }
+                }
+            }
+        }
+        index
+    }
+
+    /// Find the point of the source grid that is interpolated to the given point
+    /// of the transformed grid, using the given index of the cells
+    /// (see [`Grid::cell_index`]).
+    pub(crate) fn get_inverse_interp_point_indexed(
+        &self,
+        index: &CellIndex,
+        interp_point: &Coord,
+    ) -> Option<Coord> {
+        if !(interp_point.x >= index.min.x
+            && interp_point.y >= index.min.y
+            && interp_point.x <= index.min.x + index.size_x * index.n_x as f64
+            && interp_point.y <= index.min.y + index.size_y * index.n_y as f64)
+        {
+            return None;
+        }
+        let (x, y) = index.bucket(interp_point);
+        index.buckets[y * index.n_x + x]
+            .iter()
+            .find_map(|&(i, j)| self.invert_in_cell(i, j, interp_point))
+    }
+
+    /// Invert the bilinear interpolation of the cell whose top left node is (i, j),
+    /// returning the source point if the given point falls inside the
+    /// transformed cell.
+    fn invert_in_cell(&self, i: usize, j: usize, interp_point: &Coord) -> Option<Coord> {
+        // Top left, top right, bottom left and bottom right nodes
+        // (in the same order as in `_get_interp_point`)
+        let n = [
+            self.nodes.get_node(i, j),
+            self.nodes.get_node(i, j + 1),
+            self.nodes.get_node(i + 1, j),
+            self.nodes.get_node(i + 1, j + 1),
+        ];
+        let (mut xmin, mut xmax, mut ymin, mut ymax) = (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        );
+        for node in n.iter() {
+            xmin = xmin.min(node.interp.x);
+            xmax = xmax.max(node.interp.x);
+            ymin = ymin.min(node.interp.y);
+            ymax = ymax.max(node.interp.y);
+        }
+        if interp_point.x < xmin
+            || interp_point.x > xmax
+            || interp_point.y < ymin
+            || interp_point.y > ymax
+        {
+            return None;
+        }
+
+        // Solve f(u, v) = interp_point using Newton's method, with
+        // f(u, v) = v * ((1 - u) * n0 + u * n1) + (1 - v) * ((1 - u) * n2 + u * n3)
+        // (u going from left to right and v from bottom to top)
+        let (p0, p1, p2, p3) = (n[0].interp, n[1].interp, n[2].interp, n[3].interp);
+        let (mut u, mut v) = (0.5, 0.5);
+        for _ in 0..50 {
+            let fx = v * ((1. - u) * p0.x + u * p1.x) + (1. - v) * ((1. - u) * p2.x + u * p3.x)
+                - interp_point.x;
+            let fy = v * ((1. - u) * p0.y + u * p1.y) + (1. - v) * ((1. - u) * p2.y + u * p3.y)
+                - interp_point.y;
+            let dfx_du = v * (p1.x - p0.x) + (1. - v) * (p3.x - p2.x);
+            let dfy_du = v * (p1.y - p0.y) + (1. - v) * (p3.y - p2.y);
+            let dfx_dv = (1. - u) * (p0.x - p2.x) + u * (p1.x - p3.x);
+            let dfy_dv = (1. - u) * (p0.y - p2.y) + u * (p1.y - p3.y);
+            let det = dfx_du * dfy_dv - dfx_dv * dfy_du;
+            if det == 0. || !det.is_finite() {
+                return None;
+            }
+            let du = (fx * dfy_dv - fy * dfx_dv) / det;
+            let dv = (fy * dfx_du - fx * dfy_du) / det;
+            u -= du;
+            v -= dv;
+            if du.abs() < 1e-12 && dv.abs() < 1e-12 {
+                break;
+            }
+        }
+        let eps = 1e-9;
+        if !(-eps..=1. + eps).contains(&u) || !(-eps..=1. + eps).contains(&v) {
+            return None;
+        }
+        Some(Coord {
+            x: n[0].source.x + u * self.nodes.resolution_x,
+            y: n[2].source.y + v * self.nodes.resolution_y,
+        })
+    }
+
+    /// Compute the Jacobian matrix of the deformation (`[[dX/dx, dX/dy], [dY/dx, dY/dy]]`)
+    /// at the position (u, v) of the cell whose top left node is (i, j)
+    /// (u going from 0 to 1 from left to right and v from bottom to top).
+    pub(crate) fn cell_jacobian(&self, i: usize, j: usize, u: f64, v: f64) -> [[f64; 2]; 2] {
+        let n0 = self.nodes.get_node(i, j).interp;
+        let n1 = self.nodes.get_node(i, j + 1).interp;
+        let n2 = self.nodes.get_node(i + 1, j).interp;
+        let n3 = self.nodes.get_node(i + 1, j + 1).interp;
+        let (rx, ry) = (self.nodes.resolution_x, self.nodes.resolution_y);
+        let dx = Coord {
+            x: (v * (n1.x - n0.x) + (1. - v) * (n3.x - n2.x)) / rx,
+            y: (v * (n1.y - n0.y) + (1. - v) * (n3.y - n2.y)) / rx,
+        };
+        let dy = Coord {
+            x: ((1. - u) * (n0.x - n2.x) + u * (n1.x - n3.x)) / ry,
+            y: ((1. - u) * (n0.y - n2.y) + u * (n1.y - n3.y)) / ry,
+        };
+        [[dx.x, dy.x], [dx.y, dy.y]]
+    }
+
+    /// Compute the Jacobian matrix of the deformation at the given point of the
+    /// source grid (which must be inside the bounding box of the grid).
+    pub(crate) fn jacobian_at(&self, src_point: &Coord) -> [[f64; 2]; 2] {
+        let adj_nodes = self.nodes.get_adjacent_nodes_ref(src_point);
+        let u = (src_point.x - adj_nodes[0].source.x) / self.nodes.resolution_x;
+        let v = (src_point.y - adj_nodes[2].source.y) / self.nodes.resolution_y;
+        self.cell_jacobian(adj_nodes[0].i, adj_nodes[0].j, u, v)
+    }
+
     /// Returns the geometry of the grid (either source grid or interpolated grid).
     /// The grid is returned as a collection of geo_types polygons.
     pub fn get_grid(&self, grid_type: GridType) -> Vec<geo_types::Polygon> {
+        match grid_type {
+            GridType::Source => self.grid_polygons(|node| node.source),
+            GridType::Interpolated => self.grid_polygons(|node| node.interp),
+        }
+    }
+
+    /// Returns the geometry of an intermediate grid between the source grid (for t = 0)
+    /// and the interpolated grid (for t = 1), each node being linearly moved between
+    /// its source and interpolated positions (see [`Grid::interpolate_layer_at`]).
+    pub fn get_grid_at(&self, t: f64) -> Result<Vec<geo_types::Polygon>, Error> {
+        check_morphing_factor(t)?;
+        Ok(self.grid_polygons(|node| Coord {
+            x: (1. - t) * node.source.x + t * node.interp.x,
+            y: (1. - t) * node.source.y + t * node.interp.y,
+        }))
+    }
+
+    fn grid_polygons<F>(&self, point_getter: F) -> Vec<geo_types::Polygon>
+    where
+        F: Fn(&crate::node::Node) -> Coord,
+    {
         let mut result = Vec::with_capacity((self.nodes.height - 1) * (self.nodes.width - 1));
-        let point_getter = match grid_type {
-            GridType::Source => |node: &crate::node::Node| node.source,
-            GridType::Interpolated => |node: &crate::node::Node| node.interp,
-        };
         for i in 0..(self.nodes.height - 1) {
             for j in 0..(self.nodes.width - 1) {
                 result.push(geo_types::Polygon::new(
@@ -339,30 +637,37 @@ impl Grid {
         } else {
             None
         };
-        if nx1.is_none() {
-            diff[0] = (nx2.unwrap().interp.x - n.unwrap().interp.x) / self.nodes.resolution;
-            diff[1] = (nx2.unwrap().interp.y - n.unwrap().interp.y) / self.nodes.resolution;
-        } else if nx2.is_none() {
-            diff[0] = (n.unwrap().interp.x - nx1.unwrap().interp.x) / self.nodes.resolution;
-            diff[1] = (n.unwrap().interp.y - nx1.unwrap().interp.y) / self.nodes.resolution;
-        } else {
-            diff[0] =
-                (nx2.unwrap().interp.x - nx1.unwrap().interp.x) / (2. * self.nodes.resolution);
-            diff[1] =
-                (nx2.unwrap().interp.y - nx1.unwrap().interp.y) / (2. * self.nodes.resolution);
-        }
-
-        if ny1.is_none() {
-            diff[2] = (n.unwrap().interp.x - ny2.unwrap().interp.x) / self.nodes.resolution;
-            diff[3] = (n.unwrap().interp.y - ny2.unwrap().interp.y) / self.nodes.resolution;
-        } else if ny2.is_none() {
-            diff[2] = (ny1.unwrap().interp.x - n.unwrap().interp.x) / self.nodes.resolution;
-            diff[3] = (ny1.unwrap().interp.y - n.unwrap().interp.y) / self.nodes.resolution;
-        } else {
-            diff[2] =
-                (ny1.unwrap().interp.x - ny2.unwrap().interp.x) / (2. * self.nodes.resolution);
-            diff[3] =
-                (ny1.unwrap().interp.y - ny2.unwrap().interp.y) / (2. * self.nodes.resolution);
+        let n = n.unwrap();
+        match (nx1, nx2) {
+            (None, Some(nx2)) => {
+                diff[0] = (nx2.interp.x - n.interp.x) / self.nodes.resolution_x;
+                diff[1] = (nx2.interp.y - n.interp.y) / self.nodes.resolution_x;
+            }
+            (Some(nx1), None) => {
+                diff[0] = (n.interp.x - nx1.interp.x) / self.nodes.resolution_x;
+                diff[1] = (n.interp.y - nx1.interp.y) / self.nodes.resolution_x;
+            }
+            (Some(nx1), Some(nx2)) => {
+                diff[0] = (nx2.interp.x - nx1.interp.x) / (2. * self.nodes.resolution_x);
+                diff[1] = (nx2.interp.y - nx1.interp.y) / (2. * self.nodes.resolution_x);
+            }
+            (None, None) => unreachable!(),
+        }
+
+        match (ny1, ny2) {
+            (None, Some(ny2)) => {
+                diff[2] = (n.interp.x - ny2.interp.x) / self.nodes.resolution_y;
+                diff[3] = (n.interp.y - ny2.interp.y) / self.nodes.resolution_y;
+            }
+            (Some(ny1), None) => {
+                diff[2] = (ny1.interp.x - n.interp.x) / self.nodes.resolution_y;
+                diff[3] = (ny1.interp.y - n.interp.y) / self.nodes.resolution_y;
+            }
+            (Some(ny1), Some(ny2)) => {
+                diff[2] = (ny1.interp.x - ny2.interp.x) / (2. * self.nodes.resolution_y);
+                diff[3] = (ny1.interp.y - ny2.interp.y) / (2. * self.nodes.resolution_y);
+            }
+            (None, None) => unreachable!(),
         }
         diff
     }
@@ -373,6 +678,12 @@ impl Grid {
         ((diff[0].powi(2) + diff[1].powi(2) + diff[2].powi(2) + diff[3].powi(2)) / 2.).sqrt()
     }
 
+    /// Compute the displacement (dx, dy) of the node at position (i, j)
+    pub(crate) fn node_displacement(&self, i: usize, j: usize) -> (f64, f64) {
+        let n = self.nodes.get_node(i, j);
+        (n.interp.x - n.source.x, n.interp.y - n.source.y)
+    }
+
     /// Compute the average deformation strength for the grid
     pub fn deformation_strength(&self) -> f64 {
         (self.sum_squared_deformation_strength() / (self.nodes.width * self.nodes.height) as f64)
@@ -380,9 +691,16 @@ impl Grid {
     }
 
     /// Retrieve the resolution value
-    /// (computed from the precision given at the grid creation)
+    /// (computed from the precision given at the grid creation).
+    /// For a grid with rectangular cells (see [`GridBuilder::resolution_xy`]),
+    /// this is the width of the cells (see [`Grid::resolution_xy`]).
     pub fn resolution(&self) -> f64 {
-        self.nodes.resolution
+        self.nodes.resolution_x
+    }
+
+    /// Retrieve the width and the height of the cells of the grid.
+    pub fn resolution_xy(&self) -> (f64, f64) {
+        (self.nodes.resolution_x, self.nodes.resolution_y)
     }
 
     /// Compute the sum of squared deformation strength for the grid
@@ -440,97 +758,128 @@ impl Grid {
             .collect::<Result<Vec<Vec<geo_types::Geometry>>, Error>>()
     }
 
-    fn interpolate_geom(&self, geom: &geo_types::Geometry) -> geo_types::Geometry {
-        match geom {
-            geo_types::Geometry::Point(p) => {
-                geo_types::Geometry::Point(geo_types::Point(self._get_interp_point(&p.0)))
+    #[cfg(feature = "parallel")]
+    /// Interpolate a collection of geo_types geometries on the interpolation grid
+    /// in parallel using rayon, densifying and simplifying their lines
+    /// (see [`interpolate_layer_densified`](Grid::interpolate_layer_densified)).
+    pub fn interpolate_layer_densified_par(
+        &self,
+        geometries: &[geo_types::Geometry],
+        densification: Option<Densification>,
+        simplification: Option<f64>,
+    ) -> Result<Vec<geo_types::Geometry>, Error> {
+        self.check_densified_layer(geometries, densification, simplification)?;
+        Ok(geometries
+            .par_iter()
+            .map(|geom| self.interpolate_geom_densified(geom, densification, simplification))
+            .collect())
+    }
+
+    /// Interpolate a collection of geo_types geometries on the interpolation grid,
+    /// densifying the lines (line strings and polygon rings) before their deformation
+    /// (see [`Densification`]) so that they follow the deformation of the grid, and
+    /// optionally simplifying them afterwards (using the Douglas-Peucker algorithm
+    /// with the given tolerance, in the unit of the coordinates of the cartogram).
+    ///
+    /// Note that the `Line`, `Triangle` and `Rect` geometries are returned
+    /// as `LineString` and `Polygon` geometries (as they can't hold the inserted vertices).
+    pub fn interpolate_layer_densified(
+        &self,
+        geometries: &[geo_types::Geometry],
+        densification: Option<Densification>,
+        simplification: Option<f64>,
+    ) -> Result<Vec<geo_types::Geometry>, Error> {
+        self.check_densified_layer(geometries, densification, simplification)?;
+        Ok(geometries
+            .iter()
+            .map(|geom| self.interpolate_geom_densified(geom, densification, simplification))
+            .collect())
+    }
+
+    fn check_densified_layer(
+        &self,
+        geometries: &[geo_types::Geometry],
+        densification: Option<Densification>,
+        simplification: Option<f64>,
+    ) -> Result<(), Error> {
+        if let Some(densification) = densification {
+            if !densification.is_valid() {
+                return Err(Error::InvalidDensification(densification));
             }
-            geo_types::Geometry::MultiPoint(mp) => {
-                let mut multi_point: Vec<geo_types::Point> = Vec::with_capacity(mp.len());
-                for p in mp.iter() {
-                    multi_point.push(self._get_interp_point(&p.0).into());
-                }
-                geo_types::Geometry::MultiPoint(geo_types::MultiPoint(multi_point))
+        }
+        if let Some(tolerance) = simplification {
+            if !(tolerance.is_finite() && tolerance >= 0.) {
+                return Err(Error::InvalidTolerance(tolerance));
             }
-            geo_types::Geometry::LineString(ls) => {
-                let mut line = Vec::with_capacity(ls.0.len());
-                for p in ls.0.iter() {
-                    line.push(self._get_interp_point(p));
-                }
-                geo_types::Geometry::LineString(geo_types::LineString(line))
+        }
+        let bbox = BBox::from_geometries(geometries);
+        if !self.bbox().contains_bbox(&bbox) {
+            return Err(Error::GeometriesNotInBBox);
+        }
+        Ok(())
+    }
+
+    fn interpolate_geom_densified(
+        &self,
+        geom: &geo_types::Geometry,
+        densification: Option<Densification>,
+        simplification: Option<f64>,
+    ) -> geo_types::Geometry {
+        // The resolution used for the densification is the size of the
+        // smallest side of the cells
+        let resolution = self.nodes.resolution_x.min(self.nodes.resolution_y);
+        let line = |coords: &[Coord]| -> geo_types::LineString {
+            let deformed = deform_line(
+                coords,
+                |p| self._get_interp_point(p),
+                densification,
+                resolution,
+            );
+            match simplification {
+                Some(tolerance) => simplify_line(&deformed, tolerance).into(),
+                None => deformed.into(),
             }
-            geo_types::Geometry::MultiLineString(mls) => {
-                let mut multi_line = Vec::with_capacity(mls.0.len());
-                for ls in mls.iter() {
-                    let mut line = Vec::with_capacity(ls.0.len());
-                    for p in ls.0.iter() {
-                        line.push(self._get_interp_point(p));
-                    }
-                    multi_line.push(geo_types::LineString(line));
-                }
-                geo_types::Geometry::MultiLineString(geo_types::MultiLineString(multi_line))
+        };
+        let polygon = |poly: &geo_types::Polygon| {
+            geo_types::Polygon::new(
+                line(&poly.exterior().0),
+                poly.interiors().iter().map(|r| line(&r.0)).collect(),
+            )
+        };
+        match geom {
+            geo_types::Geometry::Point(_) | geo_types::Geometry::MultiPoint(_) => {
+                self.interpolate_geom(geom)
             }
-            geo_types::Geometry::Polygon(poly) => {
-                let mut exterior = Vec::with_capacity(poly.exterior().0.len());
-                for p in poly.exterior().0.iter() {
-                    exterior.push(self._get_interp_point(p));
-                }
-                let mut interiors = Vec::with_capacity(poly.interiors().len());
-                for interior in poly.interiors() {
-                    let mut interior_points = Vec::with_capacity(interior.0.len());
-                    for p in interior.0.iter() {
-                        interior_points.push(self._get_interp_point(p));
-                    }
-                    interiors.push(interior_points.into());
-                }
-                geo_types::Geometry::Polygon(geo_types::Polygon::new(exterior.into(), interiors))
+            geo_types::Geometry::LineString(ls) => geo_types::Geometry::LineString(line(&ls.0)),
+            geo_types::Geometry::MultiLineString(mls) => {
+                geo_types::Geometry::MultiLineString(mls.iter().map(|ls| line(&ls.0)).collect())
             }
+            geo_types::Geometry::Polygon(poly) => geo_types::Geometry::Polygon(polygon(poly)),
             geo_types::Geometry::MultiPolygon(mpoly) => {
-                let mut multi_polygon = Vec::with_capacity(mpoly.0.len());
-                for poly in mpoly.iter() {
-                    let mut exterior = Vec::with_capacity(poly.exterior().0.len());
-                    for p in poly.exterior().0.iter() {
-                        exterior.push(self._get_interp_point(p));
-                    }
-                    let mut interiors = Vec::with_capacity(poly.interiors().len());
-                    for interior in poly.interiors() {
-                        let mut interior_points = Vec::with_capacity(interior.0.len());
-                        for p in interior.0.iter() {
-                            interior_points.push(self._get_interp_point(p));
-                        }
-                        interiors.push(interior_points.into());
-                    }
-                    multi_polygon.push(geo_types::Polygon::new(exterior.into(), interiors));
-                }
-                geo_types::Geometry::MultiPolygon(geo_types::MultiPolygon(multi_polygon))
+                geo_types::Geometry::MultiPolygon(mpoly.iter().map(polygon).collect())
             }
             geo_types::Geometry::GeometryCollection(geometries) => {
                 geo_types::Geometry::GeometryCollection(
                     geometries
                         .iter()
-                        .map(|g| self.interpolate_geom(g))
+                        .map(|g| self.interpolate_geom_densified(g, densification, simplification))
                         .collect(),
                 )
             }
             geo_types::Geometry::Line(l) => {
-                let p1 = self._get_interp_point(&l.start);
-                let p2 = self._get_interp_point(&l.end);
-                geo_types::Geometry::Line(geo_types::Line { start: p1, end: p2 })
+                geo_types::Geometry::LineString(line(&[l.start, l.end]))
             }
             geo_types::Geometry::Triangle(tri) => {
-                let v1 = self._get_interp_point(&tri.0);
-                let v2 = self._get_interp_point(&tri.1);
-                let v3 = self._get_interp_point(&tri.2);
-                geo_types::Geometry::Triangle(geo_types::Triangle(v1, v2, v3))
-            }
-            geo_types::Geometry::Rect(r) => {
-                let min = self._get_interp_point(&r.min());
-                let max = self._get_interp_point(&r.max());
-                geo_types::Geometry::Rect(geo_types::Rect::new(min, max))
+                geo_types::Geometry::Polygon(polygon(&tri.to_polygon()))
             }
+            geo_types::Geometry::Rect(r) => geo_types::Geometry::Polygon(polygon(&r.to_polygon())),
         }
     }
 
+    fn interpolate_geom(&self, geom: &geo_types::Geometry) -> geo_types::Geometry {
+        map_geometry(geom, &|p| self._get_interp_point(p))
+    }
+
     /// Interpolate a collection of geo_types geometries on the interpolation grid.
     pub fn interpolate_layer(
         &self,
@@ -593,6 +942,97 @@ impl Grid {
     }
 }
 
+/// Apply a function to all the coordinates of a geometry
+/// (keeping its type and its structure).
+pub(crate) fn map_geometry<F>(geom: &geo_types::Geometry, f: &F) -> geo_types::Geometry
+where
+    F: Fn(&Coord) -> Coord,
+{
+    match geom {
+        geo_types::Geometry::Point(p) => geo_types::Geometry::Point(geo_types::Point(f(&p.0))),
+        geo_types::Geometry::MultiPoint(mp) => {
+            let mut multi_point: Vec<geo_types::Point> = Vec::with_capacity(mp.len());
+            for p in mp.iter() {
+                multi_point.push(f(&p.0).into());
+            }
+            geo_types::Geometry::MultiPoint(geo_types::MultiPoint(multi_point))
+        }
+        geo_types::Geometry::LineString(ls) => {
+            let mut line = Vec::with_capacity(ls.0.len());
+            for p in ls.0.iter() {
+                line.push(f(p));
+            }
+            geo_types::Geometry::LineString(geo_types::LineString(line))
+        }
+        geo_types::Geometry::MultiLineString(mls) => {
+            let mut multi_line = Vec::with_capacity(mls.0.len());
+            for ls in mls.iter() {
+                let mut line = Vec::with_capacity(ls.0.len());
+                for p in ls.0.iter() {
+                    line.push(f(p));
+                }
+                multi_line.push(geo_types::LineString(line));
+            }
+            geo_types::Geometry::MultiLineString(geo_types::MultiLineString(multi_line))
+        }
+        geo_types::Geometry::Polygon(poly) => {
+            let mut exterior = Vec::with_capacity(poly.exterior().0.len());
+            for p in poly.exterior().0.iter() {
+                exterior.push(f(p));
+            }
+            let mut interiors = Vec::with_capacity(poly.interiors().len());
+            for interior in poly.interiors() {
+                let mut interior_points = Vec::with_capacity(interior.0.len());
+                for p in interior.0.iter() {
+                    interior_points.push(f(p));
+                }
+                interiors.push(interior_points.into());
+            }
+            geo_types::Geometry::Polygon(geo_types::Polygon::new(exterior.into(), interiors))
+        }
+        geo_types::Geometry::MultiPolygon(mpoly) => {
+            let mut multi_polygon = Vec::with_capacity(mpoly.0.len());
+            for poly in mpoly.iter() {
+                let mut exterior = Vec::with_capacity(poly.exterior().0.len());
+                for p in poly.exterior().0.iter() {
+                    exterior.push(f(p));
+                }
+                let mut interiors = Vec::with_capacity(poly.interiors().len());
+                for interior in poly.interiors() {
+                    let mut interior_points = Vec::with_capacity(interior.0.len());
+                    for p in interior.0.iter() {
+                        interior_points.push(f(p));
+                    }
+                    interiors.push(interior_points.into());
+                }
+                multi_polygon.push(geo_types::Polygon::new(exterior.into(), interiors));
+            }
+            geo_types::Geometry::MultiPolygon(geo_types::MultiPolygon(multi_polygon))
+        }
+        geo_types::Geometry::GeometryCollection(geometries) => {
+            geo_types::Geometry::GeometryCollection(
+                geometries.iter().map(|g| map_geometry(g, f)).collect(),
+            )
+        }
+        geo_types::Geometry::Line(l) => {
+            let p1 = f(&l.start);
+            let p2 = f(&l.end);
+            geo_types::Geometry::Line(geo_types::Line { start: p1, end: p2 })
+        }
+        geo_types::Geometry::Triangle(tri) => {
+            let v1 = f(&tri.0);
+            let v2 = f(&tri.1);
+            let v3 = f(&tri.2);
+            geo_types::Geometry::Triangle(geo_types::Triangle(v1, v2, v3))
+        }
+        geo_types::Geometry::Rect(r) => {
+            let min = f(&r.min());
+            let max = f(&r.max());
+            geo_types::Geometry::Rect(geo_types::Rect::new(min, max))
+        }
+    }
+}
+
 impl Debug for Grid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Grid")
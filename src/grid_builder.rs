@@ -0,0 +1,306 @@
+use crate::bbox::BBox;
+use crate::errors::Error;
+use crate::grid::{Grid, DEFAULT_TOLERANCE};
+use crate::node::{CellSize, NodeSet};
+use crate::utils::get_nb_iterations;
+use crate::validation::validate_points;
+use geo_types::Coord;
+
+/// A builder to configure the creation of a [`Grid`]
+/// (see [`Grid::builder`](Grid::builder)).
+///
+/// By default, the size of the cells is deduced from a precision of 2
+/// (see [`Grid::new`](Grid::new)), the number of iterations is computed
+/// from the number of points (see [`get_nb_iterations`]), the grid
+/// covers the source points without padding and the convergence tolerance
+/// of the smoothing of the grid is 0.0001.
+///
+/// ```no_run
+/// # use distance_cartogram::{BBox, Grid};
+/// # use geo_types::Coord;
+/// # fn grid(source_points: &[Coord], image_points: &[Coord], bbox: BBox) -> Grid {
+/// let grid = Grid::builder(source_points, image_points)
+///     .resolution(5000.)
+///     .bbox(bbox)
+///     .padding(10000.)
+///     .build()
+///     .expect("Unable to create grid");
+/// # grid
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct GridBuilder<'a> {
+    source_points: &'a [Coord],
+    image_points: &'a [Coord],
+    cell_size: CellSize,
+    n_iter: Option<usize>,
+    bbox: Option<BBox>,
+    padding: f64,
+    tolerance: f64,
+}
+
+impl<'a> GridBuilder<'a> {
+    /// Create a new builder from the source and image points
+    /// (which must be given in the same order, as they are homologous points).
+    pub fn new(source_points: &'a [Coord], image_points: &'a [Coord]) -> Self {
+        GridBuilder {
+            source_points,
+            image_points,
+            cell_size: CellSize::Precision(2.),
+            n_iter: None,
+            bbox: None,
+            padding: 0.,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Deduce the size of the cells from the precision (higher is more precise,
+    /// see [`Grid::new`](Grid::new) for details).
+    /// This replaces any resolution or cell count set previously.
+    pub fn precision(mut self, precision: f64) -> Self {
+        self.cell_size = CellSize::Precision(precision);
+        self
+    }
+
+    /// Use the given size of the cells (in the unit of the coordinates,
+    /// for example 5000 for 5 km cells in a projected CRS in meters).
+    /// This replaces any precision, cell count or resolutions set previously.
+    pub fn resolution(mut self, resolution: f64) -> Self {
+        self.cell_size = CellSize::Resolution(resolution);
+        self
+    }
+
+    /// Deduce the size of the (square) cells from the (approximate) number of cells
+    /// covering the grid.
+    /// This replaces any precision or resolution set previously.
+    pub fn cell_count(mut self, n_cells: usize) -> Self {
+        self.cell_size = CellSize::CellCount(n_cells);
+        self
+    }
+
+    /// Use rectangular cells of the given width and height (in the unit of the
+    /// coordinates), for example to use a finer resolution along the shortest
+    /// dimension of a very elongated study area.
+    /// This replaces any precision, cell count or resolution set previously.
+    pub fn resolution_xy(mut self, resolution_x: f64, resolution_y: f64) -> Self {
+        self.cell_size = CellSize::ResolutionXY(resolution_x, resolution_y);
+        self
+    }
+
+    /// Set the number of iterations of the interpolation
+    /// (computed with [`get_nb_iterations`] by default).
+    pub fn iterations(mut self, n_iter: usize) -> Self {
+        self.n_iter = Some(n_iter);
+        self
+    }
+
+    /// Set the bounding box that the grid has to cover
+    /// (it is still extended to cover all the source points if necessary).
+    pub fn bbox(mut self, bbox: BBox) -> Self {
+        self.bbox = Some(bbox);
+        self
+    }
+
+    /// Extend the grid by the given margin (in the unit of the coordinates)
+    /// on each side, for example to leave room for the deformation
+    /// of the geometries close to the border of the grid.
+    pub fn padding(mut self, padding: f64) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the convergence tolerance of the smoothing of the grid after each
+    /// iteration (the smoothing stops when the largest displacement of a node,
+    /// relative to the size of the grid, is lower than the tolerance).
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Validate the parameters and create the grid.
+    ///
+    /// An error is returned if the size of the cells would create a grid
+    /// of more than 10 million nodes.
+    pub fn build(self) -> Result<Grid, Error> {
+        if self.source_points.len() != self.image_points.len() || self.source_points.is_empty() {
+            return Err(Error::InvalidInputPointsLength);
+        }
+        let report = validate_points(self.source_points, self.image_points);
+        if report.has_errors() {
+            return Err(Error::InvalidPoints(report));
+        }
+        match self.cell_size {
+            CellSize::Precision(precision) if !(precision.is_finite() && precision > 0.) => {
+                return Err(Error::InvalidPrecision(precision));
+            }
+            CellSize::Resolution(resolution) if !(resolution.is_finite() && resolution > 0.) => {
+                return Err(Error::InvalidResolution(resolution));
+            }
+            CellSize::CellCount(0) => {
+                return Err(Error::InvalidCellCount(0));
+            }
+            CellSize::ResolutionXY(resolution_x, resolution_y) => {
+                if let Some(r) = [resolution_x, resolution_y]
+                    .into_iter()
+                    .find(|r| !(r.is_finite() && *r > 0.))
+                {
+                    return Err(Error::InvalidResolution(r));
+                }
+            }
+            _ => {}
+        }
+        if let Some(bbox) = &self.bbox {
+            if ![bbox.xmin, bbox.ymin, bbox.xmax, bbox.ymax]
+                .iter()
+                .all(|v| v.is_finite())
+                || bbox.xmin > bbox.xmax
+                || bbox.ymin > bbox.ymax
+            {
+                return Err(Error::InvalidBBox);
+            }
+        }
+        if !(self.padding.is_finite() && self.padding >= 0.) {
+            return Err(Error::InvalidPadding(self.padding));
+        }
+        if !(self.tolerance.is_finite() && self.tolerance >= 0.) {
+            return Err(Error::InvalidTolerance(self.tolerance));
+        }
+
+        let nodes = NodeSet::new(self.source_points, self.cell_size, self.bbox, self.padding)?;
+        let n_iter = self
+            .n_iter
+            .unwrap_or_else(|| get_nb_iterations(self.source_points.len()));
+        Ok(Grid::from_nodes(
+            nodes,
+            self.source_points,
+            self.image_points,
+            n_iter,
+            self.tolerance,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{lattice, sheared};
+
+    fn points() -> (Vec<Coord>, Vec<Coord>) {
+        let source = lattice();
+        let image = sheared(&source);
+        (source, image)
+    }
+
+    #[test]
+    fn test_builder_resolution_and_padding() {
+        let (source, image) = points();
+        let grid = Grid::builder(&source, &image)
+            .resolution(5.)
+            .padding(10.)
+            .build()
+            .unwrap();
+        assert_eq!(grid.resolution(), 5.);
+        let bbox = grid.bbox();
+        assert!(bbox.xmin <= -10. && bbox.xmax >= 50. && bbox.ymin <= -10. && bbox.ymax >= 50.);
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        let (source, image) = points();
+        assert!(matches!(
+            Grid::builder(&source, &image[1..]).build(),
+            Err(Error::InvalidInputPointsLength)
+        ));
+        assert!(matches!(
+            Grid::builder(&source, &image).precision(-1.).build(),
+            Err(Error::InvalidPrecision(_))
+        ));
+        assert!(matches!(
+            Grid::builder(&source, &image).cell_count(0).build(),
+            Err(Error::InvalidCellCount(0))
+        ));
+        assert!(matches!(
+            Grid::builder(&source, &image).resolution(1e-9).build(),
+            Err(Error::TooManyNodes(_, _))
+        ));
+        assert!(matches!(
+            Grid::builder(&source, &image)
+                .cell_count(usize::MAX)
+                .build(),
+            Err(Error::TooManyNodes(_, _))
+        ));
+        assert!(matches!(
+            Grid::new(&source, &image, f64::NAN, 10, None),
+            Err(Error::InvalidPrecision(_))
+        ));
+        assert!(matches!(
+            Grid::builder(&source, &image)
+                .bbox(BBox::new(10., 0., 0., 10.))
+                .build(),
+            Err(Error::InvalidBBox)
+        ));
+        let line = vec![
+            Coord { x: 0., y: 0. },
+            Coord { x: 10., y: 0. },
+            Coord { x: 20., y: 0. },
+        ];
+        assert!(matches!(
+            Grid::builder(&line, &line).build(),
+            Err(Error::InvalidPoints(_))
+        ));
+    }
+
+    #[test]
+    fn test_builder_anisotropic_cells() {
+        let (source, image) = points();
+        let grid = Grid::builder(&source, &image)
+            .resolution_xy(4., 2.)
+            .build()
+            .unwrap();
+        assert_eq!(grid.resolution_xy(), (4., 2.));
+        let (width, height) = grid.grid_dimensions();
+        assert!(height > width);
+        // The nodes on the source points are interpolated close to the image points
+        // and the inverse interpolation gives back the source points
+        for (s, i) in source.iter().zip(image.iter()) {
+            let p = grid.get_interp_point(s).unwrap();
+            assert!((p.x - i.x).abs() < 1. && (p.y - i.y).abs() < 1.);
+            let q = grid.get_inverse_interp_point(&p).unwrap();
+            assert!((q.x - s.x).abs() < 1e-6 && (q.y - s.y).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_points_on_the_upper_edges() {
+        let (source, _) = points();
+        // The grid isn't deformed, so the points are interpolated to themselves
+        for grid in [
+            Grid::builder(&source, &source).resolution(5.).build(),
+            Grid::builder(&source, &source)
+                .resolution_xy(4., 3.)
+                .build(),
+        ] {
+            let grid = grid.unwrap();
+            let bbox = grid.bbox();
+            // The points on the last column and on the last row of nodes
+            // are interpolated in the last cells
+            for p in [
+                Coord {
+                    x: bbox.xmax,
+                    y: bbox.ymin,
+                },
+                Coord {
+                    x: bbox.xmax,
+                    y: (bbox.ymin + bbox.ymax) / 2.,
+                },
+                Coord {
+                    x: (bbox.xmin + bbox.xmax) / 2.,
+                    y: bbox.ymin,
+                },
+            ] {
+                let q = grid.get_interp_point(&p).unwrap();
+                assert!((q.x - p.x).abs() < 1e-9 && (q.y - p.y).abs() < 1e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::errors::Error;
+use crate::moving_points_multipolar::{double_centered_matrix, sorted_eigen};
+use geo_types::Coord;
+
+/// Select landmarks among the points using the max-min strategy: starting from
+/// the given first landmark, each new landmark is the point whose shortest duration
+/// to the already selected landmarks is the longest (so that the landmarks
+/// are spread over the whole study area).
+///
+/// The `durations_from` closure is called once per selected landmark with
+/// the index of the landmark, and has to return the durations from this landmark
+/// to all the `n` points (for example by querying a routing engine), so that the
+/// full duration matrix never has to be computed.
+///
+/// The function returns the indexes of the landmarks and their durations to all
+/// the points, which can be used directly with the [`landmark_positions_from_durations`]
+/// function.
+pub fn select_landmarks<F>(
+    n: usize,
+    n_landmarks: usize,
+    first: usize,
+    mut durations_from: F,
+) -> Result<(Vec<usize>, Vec<Vec<f64>>), Error>
+where
+    F: FnMut(usize) -> Vec<f64>,
+{
+    if first >= n || n_landmarks > n {
+        return Err(Error::InvalidLandmarks);
+    }
+    let mut landmarks = Vec::with_capacity(n_landmarks);
+    let mut rows: Vec<Vec<f64>> = Vec::with_capacity(n_landmarks);
+    let mut min_durations = vec![f64::INFINITY; n];
+    let mut is_landmark = vec![false; n];
+    let mut next = first;
+    while landmarks.len() < n_landmarks {
+        let row = durations_from(next);
+        if row.len() != n {
+            return Err(Error::DurationMatrixNotSquare);
+        }
+        for (m, d) in min_durations.iter_mut().zip(row.iter()) {
+            *m = m.min(*d);
+        }
+        landmarks.push(next);
+        is_landmark[next] = true;
+        rows.push(row);
+        next = match min_durations
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !is_landmark[*i])
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        {
+            Some((i, _)) => i,
+            None => break,
+        };
+    }
+    Ok((landmarks, rows))
+}
+
+/// Takes the durations from a set of landmarks to all the points and returns
+/// the coordinates of all the points obtained with Landmark MDS
+/// (a PCoA is performed on the landmarks and every other point is then
+/// positioned by triangulation from its durations to the landmarks).
+///
+/// This is useful for large sets of points (such as tens of thousands of
+/// municipalities), for which the full duration matrix and its PCoA
+/// (as done by [`generate_positions_from_durations`](crate::generate_positions_from_durations))
+/// would be too expensive to compute.
+///
+/// `landmark_durations[k][i]` is the duration from the landmark `k` to the
+/// point `i` and `landmarks[k]` is the index, among all the points, of the landmark `k`
+/// (see [`select_landmarks`] to choose the landmarks).
+/// The durations between the landmarks are made symmetric (using their mean)
+/// before performing the PCoA.
+///
+/// Note that there must be at least 3 distinct landmarks, one row of durations
+/// (with one duration per point) for each of them, that the duration from each landmark
+/// to itself must be 0 and that the durations must not be negative, missing or infinite,
+/// otherwise an error is returned.
+///
+/// As for [`generate_positions_from_durations`](crate::generate_positions_from_durations),
+/// the points are returned in the order of the input durations and are centered around
+/// the center of the landmarks, which is (0, 0).
+pub fn landmark_positions_from_durations(
+    landmark_durations: &[Vec<f64>],
+    landmarks: &[usize],
+) -> Result<Vec<Coord>, Error> {
+    let k = landmarks.len();
+    if k < 3 || landmark_durations.len() != k {
+        return Err(Error::InvalidLandmarks);
+    }
+    let n = landmark_durations[0].len();
+    for (r, row) in landmark_durations.iter().enumerate() {
+        if row.len() != n {
+            return Err(Error::DurationMatrixNotSquare);
+        }
+        if let Some(c) = row.iter().position(|&d| d < 0.) {
+            return Err(Error::NegativeDuration(landmarks[r], c));
+        }
+        if let Some(c) = row.iter().position(|&d| !d.is_finite()) {
+            return Err(Error::MissingDuration(landmarks[r], c));
+        }
+    }
+    for (r, &l) in landmarks.iter().enumerate() {
+        if l >= n || landmarks[..r].contains(&l) {
+            return Err(Error::InvalidLandmarks);
+        }
+        if landmark_durations[r][l] != 0. {
+            return Err(Error::DurationMatrixNonZeroDiagonal(l));
+        }
+    }
+
+    // Durations between the landmarks (made symmetric)
+    let delta = (0..k)
+        .map(|a| {
+            (0..k)
+                .map(|b| {
+                    (landmark_durations[a][landmarks[b]] + landmark_durations[b][landmarks[a]]) / 2.
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // Mean of the squared durations for each landmark
+    let mean_sq = delta
+        .iter()
+        .map(|row| row.iter().map(|d| d * d).sum::<f64>() / k as f64)
+        .collect::<Vec<_>>();
+
+    // Classical MDS on the landmarks
+    let (eigenvalues, eigenvectors) = sorted_eigen(double_centered_matrix(&delta));
+    let axes = eigenvalues
+        .iter()
+        .take(2)
+        .enumerate()
+        .map(|(a, &value)| (value, eigenvectors.column(a)))
+        .collect::<Vec<_>>();
+    if axes.len() < 2 || axes.iter().any(|(v, _)| *v <= 0.) {
+        return Err(Error::PCoAUnsuccessful);
+    }
+
+    // Triangulation of every point from its durations to the landmarks
+    // (the landmarks themselves being positioned at their PCoA coordinates)
+    Ok((0..n)
+        .map(|i| {
+            let mut coords = [0.; 2];
+            for (c, (value, vector)) in coords.iter_mut().zip(axes.iter()) {
+                *c = -0.5
+                    * (0..k)
+                        .map(|a| {
+                            vector[a] / value.sqrt()
+                                * (landmark_durations[a][i].powi(2) - mean_sq[a])
+                        })
+                        .sum::<f64>();
+            }
+            Coord {
+                x: coords[0],
+                y: coords[1],
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_landmark_mds() {
+        let pts: Vec<Coord> = (0..30)
+            .map(|i| Coord {
+                x: (i % 6) as f64 * 3. + (i as f64 * 0.7).sin(),
+                y: (i / 6) as f64 * 2. + (i as f64 * 1.3).cos(),
+            })
+            .collect();
+        let durations_from = |a: usize| {
+            pts.iter()
+                .map(|p| ((p.x - pts[a].x).powi(2) + (p.y - pts[a].y).powi(2)).sqrt())
+                .collect::<Vec<_>>()
+        };
+        let (landmarks, rows) = select_landmarks(pts.len(), 6, 0, durations_from).unwrap();
+        assert_eq!(landmarks.len(), 6);
+        let positions = landmark_positions_from_durations(&rows, &landmarks).unwrap();
+        // The distances between the points are preserved (as the durations are Euclidean)
+        for i in 0..pts.len() {
+            for j in 0..pts.len() {
+                let d = ((positions[i].x - positions[j].x).powi(2)
+                    + (positions[i].y - positions[j].y).powi(2))
+                .sqrt();
+                assert!((d - durations_from(i)[j]).abs() < 1e-6);
+            }
+        }
+        // The duration from a landmark to itself must be 0
+        let mut invalid = rows.clone();
+        invalid[1][landmarks[1]] = 1.;
+        assert!(matches!(
+            landmark_positions_from_durations(&invalid, &landmarks),
+            Err(Error::DurationMatrixNonZeroDiagonal(l)) if l == landmarks[1]
+        ));
+    }
+}
@@ -17,13 +17,24 @@
 //! (using the `moving-points-multipolar` feature). This can be useful if you have a durations matrix
 //! between all the source points and want to create image points from them.
 mod bbox;
+mod densify;
+mod distortion;
 mod errors;
+mod graticule;
 mod grid;
+mod grid_builder;
+mod morphing;
 
 #[cfg(feature = "moving-points-unipolar")]
 mod moving_points_unipolar;
 mod node;
+mod raster;
 mod rectangle;
+#[cfg(test)]
+mod test_utils;
+mod time_series;
+mod validation;
+mod warp;
 
 /// Module for the adjustment of image points to source points
 /// using Affine or Euclidean transformations
@@ -35,16 +46,46 @@ pub mod procrustes;
 /// Useful utilities for working with the interpolation grid.
 pub mod utils;
 
+#[cfg(feature = "moving-points-multipolar")]
+mod landmark_mds;
 #[cfg(feature = "moving-points-multipolar")]
 mod moving_points_multipolar;
+#[cfg(feature = "moving-points-multipolar")]
+mod smacof;
 
 pub use bbox::BBox;
+pub use densify::Densification;
+pub use distortion::{CellDistortion, Distortion, TissotEllipse};
+pub use graticule::{GraticuleAxis, GraticuleLine};
 pub use grid::{Grid, GridType, RMSE};
+pub use grid_builder::GridBuilder;
+pub use raster::{NodeField, Raster};
+pub use time_series::{align_frames, cartogram_series, CartogramSeries, FrameAlignment};
+pub use validation::{validate_points, PointSet, ValidationIssue, ValidationReport, MIN_POINTS};
+pub use warp::Resampling;
 
 #[cfg(feature = "moving-points-unipolar")]
 pub use moving_points_unipolar::{
-    concentric_circles, move_points, CentralTendency, MovePointsResult,
+    concentric_circles, concentric_rings, move_points, move_points_from_origins,
+    move_points_with_options, CentralTendency, ConcentricRings, DirectionalSpeeds, DurationMapping,
+    IsochroneAnnulus, IsochroneRing, MovePointsOptions, MovePointsResult,
+    MultiOriginsMovePointsResult, OriginsRule, ReferenceSpeedModel, RingResolution,
+    UnreachablePolicy,
+};
+
+#[cfg(feature = "moving-points-multipolar")]
+pub use moving_points_multipolar::{
+    asymmetry_report, generate_positions_from_durations,
+    generate_positions_from_incomplete_durations, impute_durations, mds_diagnostics,
+    pcoa_from_durations, symmetrize_durations, AsymmetryReport, ImputedDurations,
+    IncompleteDurationsPositions, MdsDiagnostics, MissingDurationsStrategy, PcoaResult,
+    Symmetrization,
 };
 
 #[cfg(feature = "moving-points-multipolar")]
-pub use moving_points_multipolar::generate_positions_from_durations;
+pub use landmark_mds::{landmark_positions_from_durations, select_landmarks};
+
+#[cfg(feature = "moving-points-multipolar")]
+pub use smacof::{
+    generate_positions_anchored_to_source, smacof, AnchoredPositions, MdsType, SmacofResult,
+};
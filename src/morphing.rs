@@ -0,0 +1,120 @@
+use crate::bbox::BBox;
+use crate::errors::Error;
+use crate::grid::{map_geometry, Grid};
+use geo_types::Coord;
+
+impl Grid {
+    /// Interpolate a collection of geo_types geometries at an intermediate step
+    /// of the deformation, for example to animate the transition between the
+    /// map (for t = 0) and the cartogram (for t = 1).
+    ///
+    /// Each vertex is linearly moved between its source position and its
+    /// deformed position (as given by [`Grid::interpolate_layer`]).
+    pub fn interpolate_layer_at(
+        &self,
+        geometries: &[geo_types::Geometry],
+        t: f64,
+    ) -> Result<Vec<geo_types::Geometry>, Error> {
+        check_morphing_factor(t)?;
+        let bbox = BBox::from_geometries(geometries);
+        if !self.bbox().contains_bbox(&bbox) {
+            return Err(Error::GeometriesNotInBBox);
+        }
+        Ok(geometries
+            .iter()
+            .map(|geom| map_geometry(geom, &|p| self.morph_point(p, t)))
+            .collect())
+    }
+
+    /// Interpolate a collection of geo_types geometries at `n_frames` evenly spaced
+    /// steps of the deformation (see [`Grid::interpolate_layer_at`]), the first
+    /// frame being the source geometries and the last one the deformed geometries.
+    pub fn interpolate_layer_frames(
+        &self,
+        geometries: &[geo_types::Geometry],
+        n_frames: usize,
+    ) -> Result<Vec<Vec<geo_types::Geometry>>, Error> {
+        frame_steps(n_frames)?
+            .map(|t| self.interpolate_layer_at(geometries, t))
+            .collect()
+    }
+
+    /// Returns the geometry of the grid at `n_frames` evenly spaced steps of the
+    /// deformation (see [`Grid::get_grid_at`]), the first frame being the source grid
+    /// and the last one the interpolated grid.
+    pub fn get_grid_frames(&self, n_frames: usize) -> Result<Vec<Vec<geo_types::Polygon>>, Error> {
+        frame_steps(n_frames)?
+            .map(|t| self.get_grid_at(t))
+            .collect()
+    }
+
+    fn morph_point(&self, p: &Coord, t: f64) -> Coord {
+        // The point is inside the grid (checked for the whole layer)
+        let deformed = self.get_interp_point(p).unwrap();
+        Coord {
+            x: (1. - t) * p.x + t * deformed.x,
+            y: (1. - t) * p.y + t * deformed.y,
+        }
+    }
+}
+
+pub(crate) fn check_morphing_factor(t: f64) -> Result<(), Error> {
+    if (0. ..=1.).contains(&t) {
+        Ok(())
+    } else {
+        Err(Error::InvalidMorphingFactor(t))
+    }
+}
+
+/// The evenly spaced steps (between 0 and 1) of the frames.
+fn frame_steps(n_frames: usize) -> Result<impl Iterator<Item = f64>, Error> {
+    if n_frames < 2 {
+        return Err(Error::InvalidFrameCount(n_frames));
+    }
+    Ok((0..n_frames).map(move |k| k as f64 / (n_frames - 1) as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{lattice, sheared};
+    use crate::GridType;
+
+    #[test]
+    fn test_morphing() {
+        let source = lattice();
+        let grid = Grid::builder(&source, &sheared(&source)).build().unwrap();
+        let layer = vec![geo_types::Geometry::LineString(
+            vec![(5., 5.), (25., 15.), (35., 38.)].into(),
+        )];
+        let frames = grid.interpolate_layer_frames(&layer, 5).unwrap();
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0], layer);
+        assert_eq!(frames[4], grid.interpolate_layer(&layer).unwrap());
+        let (first, last) = match (&frames[0][0], &frames[4][0]) {
+            (geo_types::Geometry::LineString(a), geo_types::Geometry::LineString(b)) => {
+                (a.0[1], b.0[1])
+            }
+            _ => unreachable!(),
+        };
+        match &frames[2][0] {
+            geo_types::Geometry::LineString(ls) => {
+                assert!((ls.0[1].x - (first.x + last.x) / 2.).abs() < 1e-9);
+                assert!((ls.0[1].y - (first.y + last.y) / 2.).abs() < 1e-9);
+            }
+            _ => unreachable!(),
+        }
+
+        let grids = grid.get_grid_frames(3).unwrap();
+        assert_eq!(grids[0], grid.get_grid(GridType::Source));
+        assert_eq!(grids[2], grid.get_grid(GridType::Interpolated));
+        assert!(matches!(
+            grid.interpolate_layer_at(&layer, 1.5),
+            Err(Error::InvalidMorphingFactor(_))
+        ));
+        assert!(matches!(
+            grid.get_grid_frames(1),
+            Err(Error::InvalidFrameCount(1))
+        ));
+    }
+}
@@ -1,35 +1,762 @@
 use crate::errors::Error;
+use crate::smacof::{run_smacof, Dissimilarities, MdsType};
 use geo_types::Coord;
-use pcoa::{apply_pcoa, nalgebra::DMatrix};
+use nalgebra::DMatrix;
+
+/// How an asymmetric duration matrix is made symmetric
+/// (see [`symmetrize_durations`]).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Symmetrization {
+    /// The mean of the durations in both directions.
+    Mean,
+    /// The shortest of the durations in both directions.
+    Min,
+    /// The longest of the durations in both directions.
+    Max,
+    /// The durations of the upper triangle of the matrix
+    /// (i.e. from the point `i` to the point `j` for `i < j`).
+    UpperTriangle,
+    /// The durations of the lower triangle of the matrix
+    /// (i.e. from the point `j` to the point `i` for `i < j`).
+    LowerTriangle,
+}
+
+/// The asymmetry of a duration matrix (see [`asymmetry_report`]).
+#[derive(Debug, Clone)]
+pub struct AsymmetryReport {
+    /// For each pair of points (i, j), with i < j, the magnitude of the
+    /// skew-symmetric component of the durations (i.e. `|d_ij - d_ji| / 2`).
+    pub pairs: Vec<(usize, usize, f64)>,
+    /// The largest magnitude of the skew-symmetric component.
+    pub max: f64,
+    /// The mean magnitude of the skew-symmetric component.
+    pub mean: f64,
+    /// The norm of the skew-symmetric component divided by the norm
+    /// of the duration matrix (0 for a symmetric matrix).
+    pub relative: f64,
+}
+
+/// Check that the duration matrix is square, that its diagonal is
+/// zero and that it only contains finite and non-negative durations.
+pub(crate) fn validate_durations(durations: &[Vec<f64>]) -> Result<(), Error> {
+    check_durations(durations, false)
+}
+
+/// Check that the duration matrix is square, that its diagonal is
+/// zero and that it doesn't contain negative or infinite durations,
+/// the missing durations (NaN values) being allowed outside the diagonal.
+pub(crate) fn validate_incomplete_durations(durations: &[Vec<f64>]) -> Result<(), Error> {
+    check_durations(durations, true)
+}
+
+fn check_durations(durations: &[Vec<f64>], allow_missing: bool) -> Result<(), Error> {
+    let n = durations.len();
+    for (i, item) in durations.iter().enumerate() {
+        if item.len() != n {
+            return Err(Error::DurationMatrixNotSquare);
+        }
+        if let Some(j) = item
+            .iter()
+            .enumerate()
+            .position(|(j, d)| !d.is_finite() && (!allow_missing || !d.is_nan() || i == j))
+        {
+            return Err(Error::MissingDuration(i, j));
+        }
+        if item[i] != 0. {
+            return Err(Error::DurationMatrixNonZeroDiagonal(i));
+        }
+        if let Some(j) = item.iter().position(|&d| d < 0.) {
+            return Err(Error::NegativeDuration(i, j));
+        }
+    }
+    Ok(())
+}
+
+/// Make a (possibly asymmetric) duration matrix symmetric,
+/// using the given [`Symmetrization`] policy.
+///
+/// The missing durations (NaN values) are replaced by the duration in the
+/// other direction if it is known (and are kept as NaN otherwise).
+///
+/// Note that the duration matrix must be square, with a zero diagonal
+/// and without negative or infinite durations, otherwise an error is returned.
+pub fn symmetrize_durations(
+    durations: &[Vec<f64>],
+    policy: Symmetrization,
+) -> Result<Vec<Vec<f64>>, Error> {
+    validate_incomplete_durations(durations)?;
+    let n = durations.len();
+    Ok((0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    // Durations from the point of lowest index to the other one
+                    // and conversely
+                    let (mut upper, mut lower) = if i < j {
+                        (durations[i][j], durations[j][i])
+                    } else {
+                        (durations[j][i], durations[i][j])
+                    };
+                    if upper.is_nan() {
+                        upper = lower;
+                    } else if lower.is_nan() {
+                        lower = upper;
+                    }
+                    match policy {
+                        Symmetrization::Mean => (upper + lower) / 2.,
+                        Symmetrization::Min => upper.min(lower),
+                        Symmetrization::Max => upper.max(lower),
+                        Symmetrization::UpperTriangle => upper,
+                        Symmetrization::LowerTriangle => lower,
+                    }
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Measure the asymmetry of a duration matrix (for example to decide
+/// if it can safely be made symmetric with [`symmetrize_durations`]).
+/// The pairs with a missing duration (NaN value) are ignored.
+///
+/// Note that the duration matrix must be square, with a zero diagonal
+/// and without negative or infinite durations, otherwise an error is returned.
+pub fn asymmetry_report(durations: &[Vec<f64>]) -> Result<AsymmetryReport, Error> {
+    validate_incomplete_durations(durations)?;
+    let n = durations.len();
+    let mut pairs = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    let (mut sum_sq_skew, mut sum_sq) = (0., 0.);
+    for (i, row) in durations.iter().enumerate() {
+        for (j, &d_ij) in row.iter().enumerate().skip(i + 1) {
+            let d_ji = durations[j][i];
+            if d_ij.is_nan() || d_ji.is_nan() {
+                continue;
+            }
+            let skew = (d_ij - d_ji).abs() / 2.;
+            sum_sq_skew += 2. * skew * skew;
+            sum_sq += d_ij.powi(2) + d_ji.powi(2);
+            pairs.push((i, j, skew));
+        }
+    }
+    let max = pairs.iter().map(|p| p.2).fold(0., f64::max);
+    let mean = if pairs.is_empty() {
+        0.
+    } else {
+        pairs.iter().map(|p| p.2).sum::<f64>() / pairs.len() as f64
+    };
+    Ok(AsymmetryReport {
+        pairs,
+        max,
+        mean,
+        relative: if sum_sq > 0. {
+            (sum_sq_skew / sum_sq).sqrt()
+        } else {
+            0.
+        },
+    })
+}
 
 /// Takes a duration matrix and returns the coordinates of the points
-/// obtained from the PCoA analysis.
+/// obtained from the PCoA analysis (on its two first axes, see
+/// [`pcoa_from_durations`] to use other axes and get the eigenvalues).
+///
+/// As the PCoA requires a symmetric matrix, the duration matrix
+/// is first made symmetric using the given [`Symmetrization`] policy.
+/// Note that the duration matrix must be square, with a zero diagonal
+/// and without negative durations, and that the durations must be finite
+/// (a duration missing in both directions can't be used, see
+/// [`generate_positions_from_incomplete_durations`]), otherwise an error is returned.
 ///
 /// Note that the points are returned in the order of the input durations
 /// and are centered around (0, 0). The caller is responsible for
 /// translating/scaling/rotating the points as needed to fit them to
 /// the reference points (see [`adjustment`](crate::adjustment) or
 /// [`procrustes`](crate::procrustes) modules for this).
-pub fn generate_positions_from_durations(durations: Vec<Vec<f64>>) -> Result<Vec<Coord>, Error> {
-    let m = durations.len();
-    for item in durations.iter() {
-        if item.len() != m {
-            return Err(Error::DurationMatrixNotSquare);
-        }
+pub fn generate_positions_from_durations(
+    durations: Vec<Vec<f64>>,
+    symmetrization: Symmetrization,
+) -> Result<Vec<Coord>, Error> {
+    if durations.len() < 2 {
+        return Err(Error::PCoAUnsuccessful);
     }
-    let n_dims = 2;
-    let flat_mat = durations
+    Ok(pcoa_from_durations(&durations, symmetrization, 2, (0, 1))?.points)
+}
+
+/// The result of the [`pcoa_from_durations`] function.
+#[derive(Debug, Clone)]
+pub struct PcoaResult {
+    /// The coordinates of the points on each of the computed axes
+    /// (`coordinates[i][k]` being the coordinate of the point `i` on the axis `k`),
+    /// in the order of the input durations.
+    pub coordinates: Vec<Vec<f64>>,
+    /// The positions of the points on the selected pair of axes.
+    pub points: Vec<Coord>,
+    /// All the eigenvalues of the PCoA, sorted in decreasing order
+    /// (negative eigenvalues indicating that the durations are not Euclidean distances).
+    pub eigenvalues: Vec<f64>,
+    /// The cumulative proportion of the inertia (sum of the positive eigenvalues)
+    /// explained by the computed axes (`cumulative_inertia[k]` being the
+    /// proportion explained by the axes `0` to `k`).
+    pub cumulative_inertia: Vec<f64>,
+}
+
+/// Takes a duration matrix and performs a PCoA on `n_axes` axes, returning
+/// the coordinates of the points on all these axes as well as all the eigenvalues
+/// and the cumulative inertia explained by the axes (which shows whether the
+/// durations are well represented in two dimensions).
+///
+/// The `axes` pair (0-based indexes, lower than `n_axes`) gives the axes used
+/// as the x and y coordinates of the returned points (for example `(0, 1)`
+/// for the two first axes, as done by [`generate_positions_from_durations`]).
+/// The coordinates on the axes whose eigenvalue isn't positive are set to 0.
+///
+/// As the PCoA requires a symmetric matrix, the duration matrix
+/// is first made symmetric using the given [`Symmetrization`] policy.
+/// Note that the duration matrix must be square, with a zero diagonal,
+/// without negative durations and without durations missing in both directions,
+/// and that the axes must be valid, otherwise an error is returned.
+///
+/// As for [`generate_positions_from_durations`], the points are centered
+/// around (0, 0) and have to be fitted to the reference points
+/// (see [`adjustment`](crate::adjustment) or [`procrustes`](crate::procrustes) modules for this).
+pub fn pcoa_from_durations(
+    durations: &[Vec<f64>],
+    symmetrization: Symmetrization,
+    n_axes: usize,
+    axes: (usize, usize),
+) -> Result<PcoaResult, Error> {
+    let durations = symmetrize_durations(durations, symmetrization)?;
+    validate_durations(&durations)?;
+    let n = durations.len();
+    if n_axes > n || axes.0 >= n_axes || axes.1 >= n_axes || axes.0 == axes.1 {
+        return Err(Error::InvalidAxes(axes.0, axes.1));
+    }
+
+    let (eigenvalues, eigenvectors) = sorted_eigen(double_centered_matrix(&durations));
+
+    let coordinates = (0..n)
+        .map(|i| {
+            eigenvalues
+                .iter()
+                .take(n_axes)
+                .enumerate()
+                .map(|(a, &value)| {
+                    if value > 0. {
+                        eigenvectors[(i, a)] * value.sqrt()
+                    } else {
+                        0.
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let points = coordinates
+        .iter()
+        .map(|c| Coord {
+            x: c[axes.0],
+            y: c[axes.1],
+        })
+        .collect();
+
+    let sum_positive = eigenvalues.iter().filter(|&&v| v > 0.).sum::<f64>();
+    let cumulative_inertia = eigenvalues
+        .iter()
+        .take(n_axes)
+        .scan(0., |acc, &v| {
+            *acc += v.max(0.);
+            Some(if sum_positive > 0. {
+                *acc / sum_positive
+            } else {
+                0.
+            })
+        })
+        .collect();
+
+    Ok(PcoaResult {
+        coordinates,
+        points,
+        eigenvalues,
+        cumulative_inertia,
+    })
+}
+
+/// A duration matrix whose missing durations were imputed.
+#[derive(Debug, Clone)]
+pub struct ImputedDurations {
+    /// The complete duration matrix.
+    pub durations: Vec<Vec<f64>>,
+    /// The pairs of points (i, j), with i < j, whose duration was imputed.
+    pub imputed: Vec<(usize, usize)>,
+}
+
+/// Impute the missing durations (NaN values) of a symmetric duration matrix
+/// with the duration of the shortest path going through the known durations
+/// (for example, if the duration between A and C is missing, it can be imputed
+/// as the sum of the durations between A and B and between B and C).
+///
+/// Note that the duration matrix must be square, with a zero diagonal
+/// and without negative durations, and that every pair of points must be
+/// connected by a path of known durations, otherwise an error is returned.
+pub fn impute_durations(durations: &[Vec<f64>]) -> Result<ImputedDurations, Error> {
+    validate_incomplete_durations(durations)?;
+    let mut imputed = Vec::new();
+    let mut completed = durations
         .iter()
-        .flat_map(|x| x.iter().copied())
+        .map(|row| {
+            row.iter()
+                .map(|&d| if d.is_nan() { f64::INFINITY } else { d })
+                .collect::<Vec<_>>()
+        })
         .collect::<Vec<_>>();
-    let distance_matrix = DMatrix::from_column_slice(m, m, &flat_mat);
-    let coords_matrix = apply_pcoa(distance_matrix, n_dims).ok_or(Error::PCoAUnsuccessful)?;
-    let coords_matrix = coords_matrix.transpose();
-    let xs: Vec<_> = coords_matrix.column(0).iter().copied().collect();
-    let ys: Vec<_> = coords_matrix.column(1).iter().copied().collect();
-    Ok(xs
+    for (i, row) in durations.iter().enumerate() {
+        for (j, d) in row.iter().enumerate().skip(i + 1) {
+            if d.is_nan() {
+                imputed.push((i, j));
+            }
+        }
+    }
+
+    // Floyd-Warshall algorithm on the known durations
+    for k in 0..durations.len() {
+        let row_k = completed[k].clone();
+        for row in completed.iter_mut() {
+            let d_ik = row[k];
+            if d_ik.is_infinite() {
+                continue;
+            }
+            for (d_ij, &d_kj) in row.iter_mut().zip(row_k.iter()) {
+                if d_ik + d_kj < *d_ij {
+                    *d_ij = d_ik + d_kj;
+                }
+            }
+        }
+    }
+
+    // Only replace the missing durations (the known ones are kept
+    // even if a shorter path exists)
+    for (i, row) in completed.iter_mut().enumerate() {
+        for (j, d) in row.iter_mut().enumerate() {
+            if !durations[i][j].is_nan() {
+                *d = durations[i][j];
+            } else if d.is_infinite() {
+                return Err(Error::DisconnectedDurationMatrix(i, j));
+            }
+        }
+    }
+
+    Ok(ImputedDurations {
+        durations: completed,
+        imputed,
+    })
+}
+
+/// How the missing durations are handled by the
+/// [`generate_positions_from_incomplete_durations`] function.
+#[derive(Debug, Copy, Clone)]
+pub enum MissingDurationsStrategy {
+    /// The missing durations are imputed with the duration of the shortest path
+    /// going through the known durations (see [`impute_durations`])
+    /// before applying the PCoA.
+    ShortestPaths,
+    /// The positions are computed with the SMACOF algorithm (see [`smacof`](crate::smacof)),
+    /// the missing durations having a weight of zero (the PCoA of the durations
+    /// imputed with the shortest paths being used as initial configuration).
+    WeightedMds {
+        mds_type: MdsType,
+        tolerance: f64,
+        max_iter: usize,
+    },
+}
+
+/// The result of the [`generate_positions_from_incomplete_durations`] function.
+#[derive(Debug, Clone)]
+pub struct IncompleteDurationsPositions {
+    /// The positions of the points (in the order of the durations).
+    pub points: Vec<Coord>,
+    /// The symmetric duration matrix, in which the missing durations were replaced
+    /// by the imputed durations (with the [`MissingDurationsStrategy::WeightedMds`]
+    /// strategy, the imputed durations are the distances between the positions).
+    pub durations: Vec<Vec<f64>>,
+    /// The pairs of points (i, j), with i < j, whose duration was imputed.
+    pub imputed: Vec<(usize, usize)>,
+}
+
+/// Takes a duration matrix with missing durations (NaN values), such as the ones
+/// returned by routing engines that failed to compute some durations, and returns
+/// the coordinates of the points computed using the given [`MissingDurationsStrategy`].
+///
+/// The duration matrix is first made symmetric using the given [`Symmetrization`] policy
+/// (a missing duration being replaced by the duration in the other direction if it is known).
+///
+/// Note that the duration matrix must be square, with a zero diagonal
+/// and without negative durations, and that every pair of points must be
+/// connected by a path of known durations, otherwise an error is returned.
+///
+/// As for [`generate_positions_from_durations`], the points are returned
+/// in the order of the input durations and are centered around (0, 0).
+pub fn generate_positions_from_incomplete_durations(
+    durations: &[Vec<f64>],
+    symmetrization: Symmetrization,
+    strategy: MissingDurationsStrategy,
+) -> Result<IncompleteDurationsPositions, Error> {
+    let durations = symmetrize_durations(durations, symmetrization)?;
+    let imputed = impute_durations(&durations)?;
+    let init = generate_positions_from_durations(imputed.durations.clone(), symmetrization)?;
+    match strategy {
+        MissingDurationsStrategy::ShortestPaths => Ok(IncompleteDurationsPositions {
+            points: init,
+            durations: imputed.durations,
+            imputed: imputed.imputed,
+        }),
+        MissingDurationsStrategy::WeightedMds {
+            mds_type,
+            tolerance,
+            max_iter,
+        } => {
+            let n = durations.len();
+            let delta = imputed
+                .durations
+                .iter()
+                .flat_map(|x| x.iter().copied())
+                .collect::<Vec<_>>();
+            let weights = durations
+                .iter()
+                .flat_map(|x| x.iter().map(|d| if d.is_nan() { 0. } else { 1. }))
+                .collect::<Vec<_>>();
+            let result = run_smacof(
+                Dissimilarities {
+                    delta: &delta,
+                    weights: Some(&weights),
+                    n,
+                },
+                mds_type,
+                init,
+                tolerance,
+                max_iter,
+                None,
+            )?;
+            let mut completed = imputed.durations;
+            for &(i, j) in imputed.imputed.iter() {
+                let (pi, pj) = (result.points[i], result.points[j]);
+                let d = ((pi.x - pj.x).powi(2) + (pi.y - pj.y).powi(2)).sqrt();
+                completed[i][j] = d;
+                completed[j][i] = d;
+            }
+            Ok(IncompleteDurationsPositions {
+                points: result.points,
+                durations: completed,
+                imputed: imputed.imputed,
+            })
+        }
+    }
+}
+
+/// Goodness-of-fit diagnostics of a set of positions with regard
+/// to the duration matrix they were generated from
+/// (see [`mds_diagnostics`]).
+#[derive(Debug, Clone)]
+pub struct MdsDiagnostics {
+    /// The Kruskal stress-1 between the durations and the (scaled) distances
+    /// between the positions (0 being a perfect fit).
+    pub stress: f64,
+    /// The scale factor applied to the distances between the positions
+    /// to express them in the unit of the durations.
+    pub scale: f64,
+    /// The proportion of the variance (sum of the positive eigenvalues
+    /// of the PCoA) explained by the two first axes.
+    pub explained_variance: f64,
+    /// The mass of the negative eigenvalues of the PCoA (sum of their absolute
+    /// values divided by the sum of the absolute values of all the eigenvalues),
+    /// which measures how far the durations are from being Euclidean distances.
+    pub negative_eigenvalues_mass: f64,
+    /// The contribution of each point to the stress (the sum of the contributions
+    /// of all the points being 1).
+    pub point_stress: Vec<f64>,
+    /// The (observed duration, fitted distance) pairs, for each pair of points,
+    /// to draw a Shepard diagram (the fitted distances being scaled
+    /// to the unit of the durations).
+    pub shepard: Vec<(f64, f64)>,
+}
+
+/// Compute goodness-of-fit diagnostics of the given positions
+/// (generally obtained with [`generate_positions_from_durations`] or
+/// [`smacof`](crate::smacof)) with regard to the duration matrix.
+///
+/// The distances between the positions are first scaled (using the scale
+/// factor minimizing the stress) so that the diagnostics can also be computed
+/// on positions that were adjusted to the source points.
+///
+/// Note that the duration matrix must be square (with at least 2 points), symmetric
+/// (see [`symmetrize_durations`]), with a zero diagonal and with finite non-negative
+/// durations, that there must be one position per point and that the positions
+/// (and the durations) must not all coincide, otherwise an error is returned.
+pub fn mds_diagnostics(durations: &[Vec<f64>], points: &[Coord]) -> Result<MdsDiagnostics, Error> {
+    let n = durations.len();
+    if n < 2 {
+        return Err(Error::DurationMatrixTooSmall(n));
+    }
+    validate_durations(durations)?;
+    for (i, row) in durations.iter().enumerate() {
+        if let Some(j) = (i + 1..n).find(|&j| row[j] != durations[j][i]) {
+            return Err(Error::AsymmetricDurationMatrix(i, j));
+        }
+    }
+    if points.len() != n {
+        return Err(Error::InvalidPositionsLength);
+    }
+
+    let mut shepard = Vec::with_capacity(n * (n - 1) / 2);
+    let mut pairs = Vec::with_capacity(n * (n - 1) / 2);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let d =
+                ((points[i].x - points[j].x).powi(2) + (points[i].y - points[j].y).powi(2)).sqrt();
+            pairs.push((i, j));
+            shepard.push((durations[i][j], d));
+        }
+    }
+
+    // Scale factor minimizing the raw stress
+    let sum_delta_d = shepard.iter().map(|(delta, d)| delta * d).sum::<f64>();
+    let sum_d_sq = shepard.iter().map(|(_, d)| d * d).sum::<f64>();
+    if !(sum_d_sq > 0. && sum_delta_d > 0.) {
+        return Err(Error::DegenerateConfiguration);
+    }
+    let scale = sum_delta_d / sum_d_sq;
+    shepard.iter_mut().for_each(|(_, d)| *d *= scale);
+
+    let mut point_stress = vec![0.; n];
+    let mut raw_stress = 0.;
+    for (&(i, j), (delta, d)) in pairs.iter().zip(shepard.iter()) {
+        let e = (delta - d).powi(2);
+        raw_stress += e;
+        point_stress[i] += e;
+        point_stress[j] += e;
+    }
+    if raw_stress > 0. {
+        point_stress.iter_mut().for_each(|s| *s /= 2. * raw_stress);
+    }
+    let stress = (raw_stress / (scale * scale * sum_d_sq)).sqrt();
+
+    let eigenvalues = pcoa_eigenvalues(durations);
+    let sum_positive = eigenvalues.iter().filter(|&&v| v > 0.).sum::<f64>();
+    let sum_negative = eigenvalues
+        .iter()
+        .filter(|&&v| v < 0.)
+        .map(|v| -v)
+        .sum::<f64>();
+    let explained_variance =
+        eigenvalues.iter().take(2).filter(|&&v| v > 0.).sum::<f64>() / sum_positive;
+
+    Ok(MdsDiagnostics {
+        stress,
+        scale,
+        explained_variance,
+        negative_eigenvalues_mass: sum_negative / (sum_positive + sum_negative),
+        point_stress,
+        shepard,
+    })
+}
+
+/// Compute the double-centered matrix of the squared durations
+/// (whose eigendecomposition gives the PCoA).
+pub(crate) fn double_centered_matrix(durations: &[Vec<f64>]) -> DMatrix<f64> {
+    let n = durations.len();
+    let mut b = DMatrix::<f64>::from_fn(n, n, |i, j| -0.5 * durations[i][j].powi(2));
+    let row_means = b.row_mean();
+    let col_means = b.column_mean();
+    let mean = b.mean();
+    for i in 0..n {
+        for j in 0..n {
+            b[(i, j)] = b[(i, j)] - row_means[j] - col_means[i] + mean;
+        }
+    }
+    b
+}
+
+/// Compute the eigendecomposition of a symmetric matrix, returning the eigenvalues
+/// sorted in decreasing order and the matrix of the corresponding eigenvectors
+/// (as columns, in the same order).
+pub(crate) fn sorted_eigen(matrix: DMatrix<f64>) -> (Vec<f64>, DMatrix<f64>) {
+    let eigen = matrix.symmetric_eigen();
+    let mut order = (0..eigen.eigenvalues.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+    let eigenvalues = order.iter().map(|&a| eigen.eigenvalues[a]).collect();
+    let eigenvectors = DMatrix::from_fn(eigen.eigenvectors.nrows(), order.len(), |i, a| {
+        eigen.eigenvectors[(i, order[a])]
+    });
+    (eigenvalues, eigenvectors)
+}
+
+/// Compute the eigenvalues (sorted in decreasing order) of the double-centered
+/// matrix of the squared durations, as done by the PCoA.
+pub(crate) fn pcoa_eigenvalues(durations: &[Vec<f64>]) -> Vec<f64> {
+    let mut eigenvalues = double_centered_matrix(durations)
+        .symmetric_eigenvalues()
         .iter()
-        .zip(ys.iter())
-        .map(|(x, y)| Coord { x: *x, y: *y })
-        .collect::<Vec<_>>())
+        .copied()
+        .collect::<Vec<_>>();
+    eigenvalues.sort_by(|a, b| b.total_cmp(a));
+    eigenvalues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symmetrization() {
+        let durations = vec![vec![0., 1., 4.], vec![3., 0., 2.], vec![4., 2., 0.]];
+        let sym = symmetrize_durations(&durations, Symmetrization::Mean).unwrap();
+        assert_eq!(sym[0][1], 2.);
+        assert_eq!(sym[1][0], 2.);
+        let sym = symmetrize_durations(&durations, Symmetrization::LowerTriangle).unwrap();
+        assert_eq!(sym[0][1], 3.);
+        let report = asymmetry_report(&durations).unwrap();
+        assert_eq!(report.pairs[0], (0, 1, 1.));
+        assert_eq!(report.max, 1.);
+        assert!(matches!(
+            symmetrize_durations(&[vec![1., 2.], vec![2., 0.]], Symmetrization::Mean),
+            Err(Error::DurationMatrixNonZeroDiagonal(0))
+        ));
+        assert!(matches!(
+            asymmetry_report(&[vec![0., 2.], vec![-2., 0.]]),
+            Err(Error::NegativeDuration(1, 0))
+        ));
+        // A missing duration is allowed (outside the diagonal) but not an infinite one
+        assert!(
+            symmetrize_durations(&[vec![0., f64::NAN], vec![2., 0.]], Symmetrization::Mean).is_ok()
+        );
+        assert!(matches!(
+            symmetrize_durations(&[vec![f64::NAN, 2.], vec![2., 0.]], Symmetrization::Mean),
+            Err(Error::MissingDuration(0, 0))
+        ));
+        assert!(matches!(
+            asymmetry_report(&[vec![0., 2.], vec![f64::INFINITY, 0.]]),
+            Err(Error::MissingDuration(1, 0))
+        ));
+    }
+
+    #[test]
+    fn test_validate_durations() {
+        assert!(validate_durations(&[vec![0., 2.], vec![2., 0.]]).is_ok());
+        assert!(matches!(
+            validate_durations(&[vec![0., f64::NAN], vec![2., 0.]]),
+            Err(Error::MissingDuration(0, 1))
+        ));
+        assert!(matches!(
+            validate_durations(&[vec![0., 2.], vec![2., f64::NAN]]),
+            Err(Error::MissingDuration(1, 1))
+        ));
+        assert!(matches!(
+            generate_positions_from_durations(
+                vec![
+                    vec![0., 1., f64::NAN],
+                    vec![1., 0., 2.],
+                    vec![f64::NAN, 2., 0.]
+                ],
+                Symmetrization::Mean
+            ),
+            Err(Error::MissingDuration(0, 2))
+        ));
+    }
+
+    #[test]
+    fn test_impute_durations() {
+        let durations = vec![
+            vec![0., 1., f64::NAN],
+            vec![1., 0., 2.],
+            vec![f64::NAN, 2., 0.],
+        ];
+        let imputed = impute_durations(&durations).unwrap();
+        assert_eq!(imputed.imputed, vec![(0, 2)]);
+        assert_eq!(imputed.durations[0][2], 3.);
+        assert_eq!(imputed.durations[2][0], 3.);
+        assert!(matches!(
+            impute_durations(&[vec![0., f64::NAN], vec![f64::NAN, 0.]]),
+            Err(Error::DisconnectedDurationMatrix(0, 1))
+        ));
+    }
+
+    #[test]
+    fn test_mds_diagnostics() {
+        let pts: Vec<Coord> = vec![
+            Coord { x: 0., y: 0. },
+            Coord { x: 3., y: 0. },
+            Coord { x: 3., y: 4. },
+            Coord { x: 0., y: 4. },
+        ];
+        let durations = pts
+            .iter()
+            .map(|a| {
+                pts.iter()
+                    .map(|b| 2. * ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt())
+                    .collect::<Vec<f64>>()
+            })
+            .collect::<Vec<_>>();
+        let diagnostics = mds_diagnostics(&durations, &pts).unwrap();
+        assert!(diagnostics.stress < 1e-12);
+        assert!((diagnostics.scale - 2.).abs() < 1e-12);
+        assert!((diagnostics.explained_variance - 1.).abs() < 1e-9);
+        assert!(diagnostics.negative_eigenvalues_mass < 1e-9);
+        assert_eq!(diagnostics.shepard.len(), 6);
+        assert_eq!(diagnostics.shepard[0], (6., 6.));
+
+        assert!(matches!(
+            mds_diagnostics(&[], &[]),
+            Err(Error::DurationMatrixTooSmall(0))
+        ));
+        assert!(matches!(
+            mds_diagnostics(&[vec![0., 1.], vec![2., 0.]], &pts[..2]),
+            Err(Error::AsymmetricDurationMatrix(0, 1))
+        ));
+        assert!(matches!(
+            mds_diagnostics(&[vec![0., f64::NAN], vec![f64::NAN, 0.]], &pts[..2]),
+            Err(Error::MissingDuration(0, 1))
+        ));
+        assert!(matches!(
+            mds_diagnostics(&durations, &[Coord { x: 1., y: 1. }; 4]),
+            Err(Error::DegenerateConfiguration)
+        ));
+    }
+
+    #[test]
+    fn test_pcoa_axes() {
+        // Points of a 3D box, whose durations can't be represented in two dimensions
+        let pts = (0..8)
+            .map(|i| [(i % 2) as f64 * 4., (i / 2 % 2) as f64 * 2., (i / 4) as f64])
+            .collect::<Vec<[f64; 3]>>();
+        let durations = pts
+            .iter()
+            .map(|a| {
+                pts.iter()
+                    .map(|b| {
+                        a.iter()
+                            .zip(b.iter())
+                            .map(|(x, y)| (x - y).powi(2))
+                            .sum::<f64>()
+                            .sqrt()
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<f64>>>();
+        let res = pcoa_from_durations(&durations, Symmetrization::Mean, 3, (0, 2)).unwrap();
+        assert_eq!(res.eigenvalues.len(), 8);
+        assert_eq!(res.coordinates[0].len(), 3);
+        assert!(res.cumulative_inertia[1] < 0.99);
+        assert!((res.cumulative_inertia[2] - 1.).abs() < 1e-9);
+        // The first axis is the longest side of the box and the third one the shortest
+        assert!(((res.points[0].x - res.points[1].x).abs() - 4.).abs() < 1e-9);
+        assert!(((res.points[0].y - res.points[4].y).abs() - 1.).abs() < 1e-9);
+        assert!(matches!(
+            pcoa_from_durations(&durations, Symmetrization::Mean, 2, (0, 2)),
+            Err(Error::InvalidAxes(0, 2))
+        ));
+        // The positions are the ones of the two first axes of the PCoA
+        let res = pcoa_from_durations(&durations, Symmetrization::Mean, 2, (0, 1)).unwrap();
+        assert_eq!(
+            generate_positions_from_durations(durations, Symmetrization::Mean).unwrap(),
+            res.points
+        );
+    }
 }
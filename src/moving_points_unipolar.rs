@@ -1,14 +1,298 @@
 use crate::errors::Error;
-use crate::utils::{buffer_around_point, distance, interpolate_line, median};
-use geo_types::Coord;
+use crate::grid::Grid;
+use crate::utils::{
+    angle_between, distance, interpolate_line, median, ring_around_point, weighted_median,
+};
+use geo_types::{Coord, LineString, Polygon};
+use std::f64::consts::PI;
+use std::sync::Arc;
 
 /// The central tendency method to use to compute the reference speed
 /// for the movement of the points in the [`move_points`] function.
+#[derive(Debug, Copy, Clone)]
 pub enum CentralTendency {
     Mean,
     Median,
 }
 
+/// How the reference speed varies with the direction around the
+/// reference point in the [`move_points_with_options`] function.
+#[derive(Debug, Copy, Clone)]
+pub enum ReferenceSpeedModel {
+    /// A single reference speed, used in every direction.
+    Global,
+    /// One reference speed per angular sector around the reference point.
+    /// The value is the number of sectors (of equal angular width,
+    /// starting from the x axis and going counterclockwise).
+    /// The sectors that don't contain any point use the global reference speed.
+    Sectors(usize),
+    /// A reference speed that varies smoothly with the direction,
+    /// computed with a Gaussian kernel on the angular difference between
+    /// the direction and the direction of each point.
+    /// The value is the bandwidth of the kernel (in degrees).
+    Smoothed(f64),
+}
+
+/// Reference speeds that depend on the direction from the reference point
+/// (see [`ReferenceSpeedModel`]).
+#[derive(Debug, Clone)]
+pub struct DirectionalSpeeds {
+    kind: DirectionalSpeedsKind,
+    global_speed: f64,
+}
+
+#[derive(Debug, Clone)]
+enum DirectionalSpeedsKind {
+    Sectors(Vec<f64>),
+    Smoothed {
+        angles: Vec<f64>,
+        speeds: Vec<f64>,
+        bandwidth: f64,
+        method: CentralTendency,
+    },
+}
+
+impl DirectionalSpeeds {
+    fn new(
+        model: ReferenceSpeedModel,
+        angles: &[f64],
+        speeds: &[f64],
+        global_speed: f64,
+        method: CentralTendency,
+    ) -> Option<Self> {
+        let kind = match model {
+            ReferenceSpeedModel::Global => return None,
+            ReferenceSpeedModel::Sectors(n_sectors) => {
+                let mut by_sector = vec![Vec::new(); n_sectors];
+                for (angle, speed) in angles.iter().zip(speeds.iter()) {
+                    by_sector[sector_index(*angle, n_sectors)].push(*speed);
+                }
+                DirectionalSpeedsKind::Sectors(
+                    by_sector
+                        .into_iter()
+                        .map(|s| {
+                            if s.is_empty() {
+                                global_speed
+                            } else {
+                                central_tendency(s, method)
+                            }
+                        })
+                        .collect(),
+                )
+            }
+            ReferenceSpeedModel::Smoothed(bandwidth) => DirectionalSpeedsKind::Smoothed {
+                angles: angles.to_vec(),
+                speeds: speeds.to_vec(),
+                bandwidth: bandwidth.to_radians(),
+                method,
+            },
+        };
+        Some(DirectionalSpeeds { kind, global_speed })
+    }
+
+    fn speed_at_radians(&self, angle: f64) -> f64 {
+        let angle = angle.rem_euclid(2. * PI);
+        match &self.kind {
+            DirectionalSpeedsKind::Sectors(sectors) => sectors[sector_index(angle, sectors.len())],
+            DirectionalSpeedsKind::Smoothed {
+                angles,
+                speeds,
+                bandwidth,
+                method,
+            } => {
+                let weights = angles
+                    .iter()
+                    .map(|a| {
+                        // Angular difference, wrapped in [-π, π]
+                        let delta = (angle - a + PI).rem_euclid(2. * PI) - PI;
+                        (-0.5 * (delta / bandwidth).powi(2)).exp()
+                    })
+                    .collect::<Vec<_>>();
+                let sum_weights = weights.iter().sum::<f64>();
+                if sum_weights == 0. || !sum_weights.is_finite() {
+                    return self.global_speed;
+                }
+                match method {
+                    CentralTendency::Mean => {
+                        speeds
+                            .iter()
+                            .zip(weights.iter())
+                            .map(|(s, w)| s * w)
+                            .sum::<f64>()
+                            / sum_weights
+                    }
+                    CentralTendency::Median => weighted_median(speeds, &weights),
+                }
+            }
+        }
+    }
+
+    /// The reference speed in the given direction (angle in degrees,
+    /// counterclockwise from the x axis).
+    pub fn speed_at(&self, angle: f64) -> f64 {
+        self.speed_at_radians(angle.to_radians())
+    }
+
+    /// The reference speed of each sector, if the speeds were computed
+    /// by angular sectors.
+    pub fn sector_speeds(&self) -> Option<&[f64]> {
+        match &self.kind {
+            DirectionalSpeedsKind::Sectors(sectors) => Some(sectors),
+            DirectionalSpeedsKind::Smoothed { .. } => None,
+        }
+    }
+}
+
+fn sector_index(angle: f64, n_sectors: usize) -> usize {
+    ((angle / (2. * PI / n_sectors as f64)).floor() as usize).min(n_sectors - 1)
+}
+
+fn central_tendency(series: Vec<f64>, method: CentralTendency) -> f64 {
+    match method {
+        CentralTendency::Mean => series.iter().sum::<f64>() / series.len() as f64,
+        CentralTendency::Median => median(series),
+    }
+}
+
+/// The mapping from a duration to a distance from the reference point,
+/// used in the [`move_points_with_options`] and [`concentric_circles`] functions.
+///
+/// The mapping transforms each duration into a "mapped duration" (in the same unit)
+/// and the distance from the reference point is then the reference speed
+/// multiplied by this mapped duration. Non-linear mappings can be used
+/// so that very long durations don't push the points far off the map.
+#[derive(Clone)]
+pub enum DurationMapping {
+    /// The distance is proportional to the duration.
+    Linear,
+    /// The duration is mapped with a power law: `pivot * (t / pivot) ^ exponent`
+    /// (the durations shorter than the pivot are expanded and the longer
+    /// ones are compressed if the exponent is lower than 1).
+    Power { exponent: f64, pivot: f64 },
+    /// The duration is mapped with a logarithm: `pivot * ln(1 + t / pivot)`.
+    Logarithmic { pivot: f64 },
+    /// The duration is mapped by linear interpolation between the given
+    /// (duration, mapped duration) breakpoints, sorted by strictly increasing
+    /// (positive) duration.
+    /// The (0, 0) breakpoint is implied and the slope of the last segment
+    /// is used beyond the last breakpoint.
+    PiecewiseLinear(Vec<(f64, f64)>),
+    /// The duration is mapped by the given function (that should be
+    /// increasing and return 0 for a duration of 0).
+    Custom(Arc<dyn Fn(f64) -> f64 + Send + Sync>),
+}
+
+impl DurationMapping {
+    /// Map the given duration.
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            DurationMapping::Linear => t,
+            DurationMapping::Power { exponent, pivot } => pivot * (t / pivot).powf(*exponent),
+            DurationMapping::Logarithmic { pivot } => pivot * (t / pivot).ln_1p(),
+            DurationMapping::PiecewiseLinear(breakpoints) => {
+                let mut previous = (0., 0.);
+                for (i, &(bt, bv)) in breakpoints.iter().enumerate() {
+                    if t <= bt || i == breakpoints.len() - 1 {
+                        if bt == previous.0 {
+                            return bv;
+                        }
+                        return previous.1
+                            + (t - previous.0) * (bv - previous.1) / (bt - previous.0);
+                    }
+                    previous = (bt, bv);
+                }
+                t
+            }
+            DurationMapping::Custom(f) => f(t),
+        }
+    }
+
+    /// Check the parameters of the mapping: the pivot and the exponent must be
+    /// positive and finite, and the breakpoints must be finite, sorted by
+    /// strictly increasing (positive) duration and have non-negative
+    /// and non-decreasing mapped durations.
+    fn validate(&self) -> Result<(), Error> {
+        let valid = match self {
+            DurationMapping::Linear | DurationMapping::Custom(_) => true,
+            DurationMapping::Power { exponent, pivot } => {
+                exponent.is_finite() && *exponent > 0. && pivot.is_finite() && *pivot > 0.
+            }
+            DurationMapping::Logarithmic { pivot } => pivot.is_finite() && *pivot > 0.,
+            DurationMapping::PiecewiseLinear(breakpoints) => {
+                !breakpoints.is_empty()
+                    && breakpoints
+                        .iter()
+                        .all(|(bt, bv)| bt.is_finite() && bv.is_finite())
+                    && breakpoints[0].0 > 0.
+                    && breakpoints[0].1 >= 0.
+                    && breakpoints
+                        .windows(2)
+                        .all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1)
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidDurationMapping(self.clone()))
+        }
+    }
+
+    /// Map the given durations (the infinite durations being kept as is),
+    /// checking that the mapped durations are finite, non-negative and
+    /// positive for the positive durations (which isn't guaranteed for
+    /// a [`DurationMapping::Custom`] function).
+    fn map_durations(&self, durations: &[f64]) -> Result<Vec<f64>, Error> {
+        durations
+            .iter()
+            .map(|&t| {
+                if !t.is_finite() {
+                    return Ok(t);
+                }
+                let mapped = self.apply(t);
+                if !mapped.is_finite() || mapped < 0. || (t > 0. && mapped == 0.) {
+                    return Err(Error::InvalidDurationMapping(self.clone()));
+                }
+                Ok(mapped)
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for DurationMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DurationMapping::Linear => write!(f, "Linear"),
+            DurationMapping::Power { exponent, pivot } => f
+                .debug_struct("Power")
+                .field("exponent", exponent)
+                .field("pivot", pivot)
+                .finish(),
+            DurationMapping::Logarithmic { pivot } => {
+                f.debug_struct("Logarithmic").field("pivot", pivot).finish()
+            }
+            DurationMapping::PiecewiseLinear(breakpoints) => {
+                f.debug_tuple("PiecewiseLinear").field(breakpoints).finish()
+            }
+            DurationMapping::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+/// What to do with the points that can't be reached from the reference point
+/// (i.e. the points whose duration is `f64::INFINITY`) in the [`move_points_with_options`] function.
+#[derive(Debug, Copy, Clone)]
+pub enum UnreachablePolicy {
+    /// Don't include the unreachable points in the moved points
+    /// (the same points must then be removed from the source points, see
+    /// [`MovePointsResult::retain_source_points`]).
+    Skip,
+    /// Keep the unreachable points at their source position.
+    KeepInPlace,
+    /// Move the unreachable points, in the direction of their source position,
+    /// at the given distance from the reference point.
+    MaxRadius(f64),
+}
+
 /// The result of the movement of the points.
 pub struct MovePointsResult {
     /// The moved points.
@@ -19,6 +303,133 @@ pub struct MovePointsResult {
     pub reference_speed: f64,
     /// The reference point used for the movement.
     pub reference_point: Coord,
+    /// The indexes (in the source points) of the points that can't be
+    /// reached from the reference point and that were handled
+    /// according to the [`UnreachablePolicy`].
+    pub unreachable: Vec<usize>,
+    /// Whether the unreachable points were skipped (and thus
+    /// are not included in the moved points).
+    pub skipped: bool,
+    /// The reference speeds by direction, if a directional
+    /// [`ReferenceSpeedModel`] was used for the movement.
+    pub directional_speeds: Option<DirectionalSpeeds>,
+    /// The mapping from durations to distances used for the movement.
+    pub duration_mapping: DurationMapping,
+}
+
+impl MovePointsResult {
+    /// The reference speed in the given direction (angle in degrees,
+    /// counterclockwise from the x axis). This is the global reference speed
+    /// unless a directional [`ReferenceSpeedModel`] was used.
+    pub fn reference_speed_at(&self, angle: f64) -> f64 {
+        match &self.directional_speeds {
+            Some(speeds) => speeds.speed_at(angle),
+            None => self.reference_speed,
+        }
+    }
+
+    /// The distance from the reference point at which a point reached
+    /// in the given duration, in the given direction (angle in degrees,
+    /// counterclockwise from the x axis), is placed (when the factor is 1).
+    pub fn radius_at(&self, duration: f64, angle: f64) -> f64 {
+        self.reference_speed_at(angle) * self.duration_mapping.apply(duration)
+    }
+
+    /// Returns the source points that have a counterpart in the moved points
+    /// (i.e. the source points without the points that were skipped
+    /// because they can't be reached), so that they can be used
+    /// alongside the moved points to create a [`Grid`](crate::Grid).
+    pub fn retain_source_points(&self, source_points: &[Coord]) -> Vec<Coord> {
+        retain_reachable(source_points, &self.unreachable, self.skipped)
+    }
+}
+
+/// The options of the movement of the points in the [`move_points_with_options`] function.
+///
+/// By default, the factor is 1, the reference speed is the median speed, used in every
+/// direction, the distances are proportional to the durations and the unreachable
+/// points are kept in place:
+///
+/// ```
+/// # use distance_cartogram::{MovePointsOptions, ReferenceSpeedModel};
+/// let options = MovePointsOptions {
+///     speed_model: ReferenceSpeedModel::Sectors(8),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct MovePointsOptions {
+    /// The multiplier to the displacement that is computed from the reference speed
+    /// (a larger factor will move the points further away).
+    pub factor: f64,
+    /// The central tendency method used to compute the reference speed.
+    pub method: CentralTendency,
+    /// How the reference speed varies with the direction around the reference point.
+    pub speed_model: ReferenceSpeedModel,
+    /// The mapping from the durations to the distances from the reference point.
+    pub duration_mapping: DurationMapping,
+    /// What to do with the points that can't be reached.
+    pub unreachable_policy: UnreachablePolicy,
+}
+
+impl Default for MovePointsOptions {
+    fn default() -> Self {
+        MovePointsOptions {
+            factor: 1.,
+            method: CentralTendency::Median,
+            speed_model: ReferenceSpeedModel::Global,
+            duration_mapping: DurationMapping::Linear,
+            unreachable_policy: UnreachablePolicy::KeepInPlace,
+        }
+    }
+}
+
+impl MovePointsOptions {
+    /// Check the parameters of the reference speed model and of the duration mapping.
+    fn validate(&self) -> Result<(), Error> {
+        match self.speed_model {
+            ReferenceSpeedModel::Global => {}
+            ReferenceSpeedModel::Sectors(n_sectors) => {
+                if n_sectors == 0 {
+                    return Err(Error::InvalidReferenceSpeedModel(self.speed_model));
+                }
+            }
+            ReferenceSpeedModel::Smoothed(bandwidth) => {
+                if !(bandwidth.is_finite() && bandwidth > 0.) {
+                    return Err(Error::InvalidReferenceSpeedModel(self.speed_model));
+                }
+            }
+        }
+        self.duration_mapping.validate()
+    }
+}
+
+fn retain_reachable(source_points: &[Coord], unreachable: &[usize], skipped: bool) -> Vec<Coord> {
+    if !skipped {
+        return source_points.to_vec();
+    }
+    source_points
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| unreachable.binary_search(i).is_err())
+        .map(|(_, pt)| *pt)
+        .collect()
+}
+
+/// Move a point, reached in the duration `t` from the origin,
+/// given the reference speed and the displacement factor
+/// (a point located at the origin stays in place, whatever the duration).
+fn move_point(origin: &Coord, pt: &Coord, t: f64, ref_speed: f64, factor: f64) -> Coord {
+    let dist = distance(origin, pt);
+    if dist == 0. {
+        return *pt;
+    }
+    // Get the displacement factor for the point given the reference speed
+    let displacement = ref_speed / (dist / t);
+    // Combine the factor and the computed displacement value
+    let d = 1. + (displacement - 1.) * factor;
+    // Actually compute the position of the moved point
+    interpolate_line(origin, pt, d * dist)
 }
 
 /// Move the points (using a central tendency method such as the
@@ -36,79 +447,378 @@ pub struct MovePointsResult {
 /// value to use and a larger factor will move the points further
 /// away).
 ///
-/// Note that the source points and the durations must have the same length
-/// and that there must be a reference point for which the duration is 0.
+/// The other options have their default values (see [`MovePointsOptions`]),
+/// use [`move_points_with_options`] to change them.
+///
+/// Note that the source points and the durations must have the same length,
+/// that the durations must not be NaN or negative,
+/// that there must be exactly one reference point for which the duration is 0
+/// and that at least one other point must be reachable (with a finite duration).
 /// If one of these conditions is not met, an error is returned.
 pub fn move_points(
     source_points: &[Coord],
     durations: &[f64],
     factor: f64,
     method: CentralTendency,
+) -> Result<MovePointsResult, Error> {
+    move_points_with_options(
+        source_points,
+        durations,
+        &MovePointsOptions {
+            factor,
+            method,
+            ..Default::default()
+        },
+    )
+}
+
+/// Move the points as [`move_points`] does, with the given options
+/// (see [`MovePointsOptions`]).
+///
+/// The durations are first transformed using the given [`DurationMapping`]
+/// (use [`DurationMapping::Linear`] to keep the distances proportional to the durations),
+/// the speeds and the reference speed being computed from the mapped durations.
+///
+/// The reference speed can either be the same in every direction or
+/// depend on the direction from the reference point (see [`ReferenceSpeedModel`]),
+/// in which case each point is compared to the reference speed in its own direction.
+///
+/// The points with an infinite duration are considered unreachable,
+/// they are not used to compute the reference speed and are handled
+/// according to the given [`UnreachablePolicy`] (their indexes are
+/// stored in the returned [`MovePointsResult`]).
+///
+/// Note that the source points and the durations must have the same length,
+/// that the durations must not be NaN or negative,
+/// that there must be exactly one reference point for which the duration is 0
+/// and that at least one other point must be reachable (with a finite duration).
+/// The parameters of the reference speed model and of the duration mapping are
+/// also checked (see [`ReferenceSpeedModel`] and [`DurationMapping`]).
+/// If one of these conditions is not met, an error is returned.
+pub fn move_points_with_options(
+    source_points: &[Coord],
+    durations: &[f64],
+    options: &MovePointsOptions,
 ) -> Result<MovePointsResult, Error> {
     if source_points.len() != durations.len() {
         return Err(Error::InvalidInputDurationsLength);
     }
+    options.validate()?;
+    let MovePointsOptions {
+        factor,
+        method,
+        speed_model,
+        ref duration_mapping,
+        unreachable_policy,
+    } = *options;
+    if let Some(i) = durations.iter().position(|t| t.is_nan() || *t < 0.) {
+        return Err(Error::InvalidDuration(i));
+    }
     // Find the index for which the duration is 0,
     // this will be our reference points for the movement
     // of the other points.
-    // If there is none, this is an error and we return.
+    // If there is none (or more than one), this is an error and we return.
     let idx = durations
         .iter()
         .position(|&t| t == 0.0)
         .ok_or(Error::NoReferencePoint)?;
+    if let Some(other) = durations.iter().skip(idx + 1).position(|&t| t == 0.0) {
+        return Err(Error::MultipleReferencePoints(idx, idx + 1 + other));
+    }
 
     let ref_point = &source_points[idx];
-    // Get all the points that are not the reference point
-    // associated with their duration.
-    // So we have (point, duration, distance, speed).
-    let pt_time: Vec<(&Coord, f64, f64, f64)> = source_points
+    // Map the durations to the durations used to compute the distances
+    let mapped_durations = duration_mapping.map_durations(durations)?;
+    // Get the direction and the speed (distance / duration) of all the points
+    // that are not the reference point and that can be reached.
+    let (angles, speeds): (Vec<f64>, Vec<f64>) = source_points
         .iter()
-        .zip(durations.iter())
-        .filter(|(_, &t)| t != 0.0)
-        .map(|(pt, &t)| {
-            let dist = distance(ref_point, pt);
-            (pt, t, dist, dist / t)
-        })
-        .collect();
+        .zip(mapped_durations.iter())
+        .filter(|(_, &t)| t != 0.0 && t.is_finite())
+        .map(|(pt, &t)| (angle_between(ref_point, pt), distance(ref_point, pt) / t))
+        .unzip();
+
+    if speeds.is_empty() {
+        return Err(Error::NoReachablePoint);
+    }
 
     // Compute the reference speed from the given central tendency method
-    let ref_speed = match method {
-        CentralTendency::Mean => {
-            pt_time.iter().map(|(_, _, _, speed)| speed).sum::<f64>() / pt_time.len() as f64
-        }
-        CentralTendency::Median => {
-            let speeds = pt_time
-                .iter()
-                .map(|(_, _, _, speed)| *speed)
-                .collect::<Vec<_>>();
-            median(speeds)
+    // (and, if requested, the reference speeds by direction)
+    let ref_speed = central_tendency(speeds.clone(), method);
+    let directional_speeds =
+        DirectionalSpeeds::new(speed_model, &angles, &speeds, ref_speed, method);
+
+    // Reconstruction of the points (taking care of the reference point
+    // and of the unreachable points).
+    let mut new_points = Vec::with_capacity(source_points.len());
+    let mut unreachable = Vec::new();
+
+    for (i, (pt, &t)) in source_points
+        .iter()
+        .zip(mapped_durations.iter())
+        .enumerate()
+    {
+        if i == idx {
+            new_points.push(*ref_point);
+        } else if t.is_infinite() {
+            unreachable.push(i);
+            match unreachable_policy {
+                UnreachablePolicy::Skip => {}
+                UnreachablePolicy::KeepInPlace => new_points.push(*pt),
+                UnreachablePolicy::MaxRadius(radius) => {
+                    new_points.push(interpolate_line(ref_point, pt, radius))
+                }
+            }
+        } else {
+            let speed = match &directional_speeds {
+                Some(speeds) => speeds.speed_at_radians(angle_between(ref_point, pt)),
+                None => ref_speed,
+            };
+            new_points.push(move_point(ref_point, pt, t, speed, factor));
         }
-    };
+    }
+
+    Ok(MovePointsResult {
+        points: new_points,
+        reference_point: *ref_point,
+        reference_speed: ref_speed,
+        unreachable,
+        skipped: matches!(unreachable_policy, UnreachablePolicy::Skip),
+        directional_speeds,
+        duration_mapping: duration_mapping.clone(),
+    })
+}
 
-    // Get the displacement factor for each point given the reference speed.
-    // So we have (point, duration, distance, speed, displacement).
-    let pt_times_displacement: Vec<(&Coord, f64, f64, f64, f64)> = pt_time
+/// How the displacements computed from each origin are combined
+/// in the [`move_points_from_origins`] function.
+#[derive(Debug, Copy, Clone)]
+pub enum OriginsRule {
+    /// Each point is moved from the origin that can reach it
+    /// in the shortest duration (only this duration is taken into account,
+    /// as in "accessibility to the nearest facility" cartograms).
+    Nearest,
+    /// Each point is moved by the weighted mean of the displacements
+    /// computed from each origin, the weight of an origin being the inverse of
+    /// the duration from this origin raised to the given power
+    /// (so that the closest origins have the most influence).
+    Weighted(f64),
+}
+
+/// The result of the movement of the points from several origins.
+pub struct MultiOriginsMovePointsResult {
+    /// The moved points.
+    pub points: Vec<Coord>,
+    /// The reference speed used for the movement.
+    pub reference_speed: f64,
+    /// The origins used for the movement.
+    pub origins: Vec<Coord>,
+    /// For each source point, the index of the origin that can reach it in the
+    /// shortest duration (`None` for the points that can't be reached).
+    pub nearest_origin: Vec<Option<usize>>,
+    /// The indexes (in the source points) of the points that can't be
+    /// reached from any origin and that were handled
+    /// according to the [`UnreachablePolicy`].
+    pub unreachable: Vec<usize>,
+    /// Whether the unreachable points were skipped (and thus
+    /// are not included in the moved points).
+    pub skipped: bool,
+    /// The reference speeds by direction of travel (from the origins), if the
+    /// movement used a [`ReferenceSpeedModel`] other than [`ReferenceSpeedModel::Global`].
+    pub directional_speeds: Option<DirectionalSpeeds>,
+    /// The mapping from the durations to the distances used for the movement.
+    pub duration_mapping: DurationMapping,
+}
+
+impl MultiOriginsMovePointsResult {
+    /// Returns the source points that have a counterpart in the moved points
+    /// (i.e. the source points without the points that were skipped
+    /// because they can't be reached), so that they can be used
+    /// alongside the moved points to create a [`Grid`](crate::Grid).
+    pub fn retain_source_points(&self, source_points: &[Coord]) -> Vec<Coord> {
+        retain_reachable(source_points, &self.unreachable, self.skipped)
+    }
+}
+
+/// Move the points from several origins (such as one per hospital),
+/// each origin having its own durations to the source points
+/// (`durations[k][i]` being the duration from the origin `k` to the source point `i`).
+///
+/// A single reference speed is computed, using the central tendency method of the options,
+/// from the speeds (Euclidean distance / mapped duration) of the (origin, point) pairs
+/// used by the [`OriginsRule`], so that the same duration is
+/// represented by the same distance whatever the origin.
+/// The points are then moved, from each origin, as in the [`move_points_with_options`]
+/// function, and the positions are combined according to the given rule.
+///
+/// The options are used as in [`move_points_with_options`]: the durations are mapped
+/// with the [`DurationMapping`] (the weights of the [`OriginsRule::Weighted`] rule
+/// being computed from the durations before the mapping) and, if the
+/// [`ReferenceSpeedModel`] is not global, the reference speeds depend on the
+/// direction of travel from the origin to the point, all the origins sharing the same
+/// reference speeds by direction.
+///
+/// The origins don't have to be part of the source points, but a source
+/// point with a duration of 0 from an origin is considered to be at this origin
+/// and is not moved (as is a source point located at an origin).
+///
+/// The points with an infinite duration from every origin are considered
+/// unreachable and are handled according to the [`UnreachablePolicy`] of the options
+/// (for [`UnreachablePolicy::MaxRadius`], the distance is taken from
+/// the closest origin).
+///
+/// Note that there must be at least one origin, one durations vector per origin,
+/// each of them having the same length as the source points,
+/// that the power of the [`OriginsRule::Weighted`] rule must be a finite value,
+/// positive or zero, that the durations must not be NaN or negative
+/// and that at least one point must be reachable (with a non-zero finite duration)
+/// from the origins used by the rule.
+/// The options are also checked (see [`move_points_with_options`]).
+/// If one of these conditions is not met, an error is returned.
+pub fn move_points_from_origins(
+    source_points: &[Coord],
+    origins: &[Coord],
+    durations: &[Vec<f64>],
+    rule: OriginsRule,
+    options: &MovePointsOptions,
+) -> Result<MultiOriginsMovePointsResult, Error> {
+    if origins.is_empty() {
+        return Err(Error::NoOrigin);
+    }
+    if origins.len() != durations.len() || durations.iter().any(|d| d.len() != source_points.len())
+    {
+        return Err(Error::InvalidInputDurationsLength);
+    }
+    if let OriginsRule::Weighted(power) = rule {
+        if !(power.is_finite() && power >= 0.) {
+            return Err(Error::InvalidOriginsRule(rule));
+        }
+    }
+    options.validate()?;
+    let MovePointsOptions {
+        factor,
+        method,
+        speed_model,
+        ref duration_mapping,
+        unreachable_policy,
+    } = *options;
+    for (k, d) in durations.iter().enumerate() {
+        if let Some(i) = d.iter().position(|t| t.is_nan() || *t < 0.) {
+            return Err(Error::InvalidOriginDuration(k, i));
+        }
+    }
+    // Map the durations to the durations used to compute the distances
+    let mapped_durations = durations
         .iter()
-        .map(|&(pt, d, dist, speed)| (pt, d, dist, speed, ref_speed / speed))
-        .collect();
+        .map(|d| duration_mapping.map_durations(d))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    // Reconstruction of the points (taking care of the reference point).
-    let mut new_points = Vec::with_capacity(source_points.len());
+    // Find the origin that can reach each point in the shortest duration
+    let nearest_origin = (0..source_points.len())
+        .map(|i| {
+            (0..origins.len())
+                .filter(|&k| durations[k][i].is_finite())
+                .min_by(|&k1, &k2| durations[k1][i].total_cmp(&durations[k2][i]))
+        })
+        .collect::<Vec<_>>();
 
-    for (pt, _d, dist, _speed, displacement) in pt_times_displacement.into_iter() {
-        // Combine the factor and the computed displacement value
-        let d = 1. + (displacement - 1.) * factor;
-        // Actually compute the position of the moved point
-        new_points.push(interpolate_line(ref_point, pt, d * dist));
+    // Compute the reference speed (and, if requested, the reference speeds
+    // by direction) from the pairs used by the rule
+    let mut angles = Vec::new();
+    let mut speeds = Vec::new();
+    for (i, pt) in source_points.iter().enumerate() {
+        for (k, origin) in origins.iter().enumerate() {
+            let t = mapped_durations[k][i];
+            let used = match rule {
+                OriginsRule::Nearest => nearest_origin[i] == Some(k),
+                OriginsRule::Weighted(_) => true,
+            };
+            if used && t != 0.0 && t.is_finite() {
+                angles.push(angle_between(origin, pt));
+                speeds.push(distance(origin, pt) / t);
+            }
+        }
     }
+    if speeds.is_empty() {
+        return Err(Error::NoReachablePoint);
+    }
+    let ref_speed = central_tendency(speeds.clone(), method);
+    let directional_speeds =
+        DirectionalSpeeds::new(speed_model, &angles, &speeds, ref_speed, method);
+    let speed_from = |origin: &Coord, pt: &Coord| match &directional_speeds {
+        Some(speeds) => speeds.speed_at_radians(angle_between(origin, pt)),
+        None => ref_speed,
+    };
 
-    // Add the reference point at the right index
-    new_points.insert(idx, *ref_point);
+    let mut new_points = Vec::with_capacity(source_points.len());
+    let mut unreachable = Vec::new();
 
-    Ok(MovePointsResult {
+    for (i, pt) in source_points.iter().enumerate() {
+        let Some(nearest) = nearest_origin[i] else {
+            unreachable.push(i);
+            match unreachable_policy {
+                UnreachablePolicy::Skip => {}
+                UnreachablePolicy::KeepInPlace => new_points.push(*pt),
+                UnreachablePolicy::MaxRadius(radius) => {
+                    let origin = origins
+                        .iter()
+                        .min_by(|a, b| distance(a, pt).total_cmp(&distance(b, pt)))
+                        .unwrap();
+                    new_points.push(interpolate_line(origin, pt, radius));
+                }
+            }
+            continue;
+        };
+        if durations[nearest][i] == 0.0 || origins.contains(pt) {
+            new_points.push(*pt);
+            continue;
+        }
+        match rule {
+            OriginsRule::Nearest => {
+                let origin = &origins[nearest];
+                new_points.push(move_point(
+                    origin,
+                    pt,
+                    mapped_durations[nearest][i],
+                    speed_from(origin, pt),
+                    factor,
+                ));
+            }
+            OriginsRule::Weighted(power) => {
+                let (mut sx, mut sy, mut sw) = (0., 0., 0.);
+                for (k, origin) in origins.iter().enumerate() {
+                    let t = durations[k][i];
+                    if t.is_infinite() {
+                        continue;
+                    }
+                    let w = 1. / t.powf(power);
+                    let moved = move_point(
+                        origin,
+                        pt,
+                        mapped_durations[k][i],
+                        speed_from(origin, pt),
+                        factor,
+                    );
+                    sx += w * (moved.x - pt.x);
+                    sy += w * (moved.y - pt.y);
+                    sw += w;
+                }
+                new_points.push(Coord {
+                    x: pt.x + sx / sw,
+                    y: pt.y + sy / sw,
+                });
+            }
+        }
+    }
+
+    Ok(MultiOriginsMovePointsResult {
         points: new_points,
-        reference_point: *ref_point,
         reference_speed: ref_speed,
+        origins: origins.to_vec(),
+        nearest_origin,
+        unreachable,
+        skipped: matches!(unreachable_policy, UnreachablePolicy::Skip),
+        directional_speeds,
+        duration_mapping: duration_mapping.clone(),
     })
 }
 
@@ -119,18 +829,633 @@ pub fn move_points(
 /// The steps are the durations at which the circles will be created
 /// (in the unit of the duration between the reference point and the
 /// other points).
+///
+/// The radius of each circle is computed as for the moved points, using
+/// the [`DurationMapping`] used to move the points.
+/// If directional reference speeds were used to move the points,
+/// the circles are deformed accordingly (the distance from the
+/// reference point, in each direction, being the reference speed
+/// in this direction multiplied by the mapped step).
+///
+/// See [`concentric_rings`] for more control over the resolution of the circles
+/// and to also get the annuli between the circles and label anchors.
 pub fn concentric_circles(
     move_points_result: &MovePointsResult,
     steps: Vec<f64>,
 ) -> Vec<(geo_types::Geometry, f64)> {
     let ref_point = move_points_result.reference_point;
-    let ref_speed = move_points_result.reference_speed;
     let mut circles = Vec::with_capacity(steps.len());
 
     for step in steps {
-        let circle = buffer_around_point(&ref_point, ref_speed * step, 100);
+        let circle = ring_around_point(
+            &ref_point,
+            |angle| move_points_result.radius_at(step, angle.to_degrees()),
+            100,
+        );
         circles.push((geo_types::Geometry::from(circle), step));
     }
 
     circles
 }
+
+/// How the number of segments used to draw the circles is determined
+/// in the [`concentric_rings`] function.
+#[derive(Debug, Copy, Clone)]
+pub enum RingResolution {
+    /// A fixed number of segments (at least 3).
+    Segments(usize),
+    /// The number of segments is chosen so that the maximum distance
+    /// between the arcs and their chords is lower than the given (positive) value,
+    /// with at least 8 and at most 10 000 segments.
+    MaxChordError(f64),
+}
+
+/// The maximum number of segments of a circle drawn with
+/// [`RingResolution::MaxChordError`].
+const MAX_RING_SEGMENTS: usize = 10_000;
+
+/// A concentric circle (at a given duration from the reference point).
+#[derive(Debug, Clone)]
+pub struct IsochroneRing {
+    /// The ring.
+    pub ring: LineString,
+    /// The duration represented by the ring.
+    pub step: f64,
+    /// A point on the ring that can be used to place its label.
+    pub label_anchor: Coord,
+}
+
+/// The area between two consecutive concentric circles.
+#[derive(Debug, Clone)]
+pub struct IsochroneAnnulus {
+    /// The annulus (its interior ring is the inner circle, except
+    /// for the innermost annulus which is a disc).
+    pub polygon: Polygon,
+    /// The duration represented by the inner circle
+    /// (0 for the innermost annulus).
+    pub inner_step: f64,
+    /// The duration represented by the outer circle.
+    pub outer_step: f64,
+}
+
+/// The result of the [`concentric_rings`] function.
+#[derive(Debug, Clone)]
+pub struct ConcentricRings {
+    /// The circles, sorted by increasing duration.
+    pub rings: Vec<IsochroneRing>,
+    /// The annuli between consecutive circles, sorted by increasing duration.
+    pub annuli: Vec<IsochroneAnnulus>,
+}
+
+/// Takes the result of the unipolar movement of the points and creates
+/// concentric circles, at the given steps, around the reference point
+/// (as [`concentric_circles`] does), along with the filled annuli
+/// between consecutive circles and an anchor point for the label of each circle.
+///
+/// The steps are the durations at which the circles will be created
+/// (in the unit of the duration between the reference point and the
+/// other points), they must be positive and finite and are sorted in increasing order.
+///
+/// The resolution controls the number of segments of each circle and the
+/// label angle (in degrees, counterclockwise from the x axis) is the direction,
+/// from the reference point, in which the label anchors are placed.
+///
+/// If a grid is provided, the circles (which are expressed in the
+/// cartogram space) are deformed through the inverse of the grid, so that they
+/// can be drawn on the undeformed map. In this case, an error is returned
+/// if the circles don't fall inside the interpolated grid.
+pub fn concentric_rings(
+    move_points_result: &MovePointsResult,
+    steps: &[f64],
+    resolution: RingResolution,
+    label_angle: f64,
+    grid: Option<&Grid>,
+) -> Result<ConcentricRings, Error> {
+    match resolution {
+        RingResolution::Segments(n) if n < 3 => {
+            return Err(Error::InvalidRingResolution(resolution));
+        }
+        RingResolution::MaxChordError(max_error) if !(max_error.is_finite() && max_error > 0.) => {
+            return Err(Error::InvalidRingResolution(resolution));
+        }
+        _ => {}
+    }
+    if let Some(step) = steps.iter().find(|t| !(t.is_finite() && **t > 0.)) {
+        return Err(Error::InvalidRingStep(*step));
+    }
+    let ref_point = move_points_result.reference_point;
+    let mut steps = steps.to_vec();
+    steps.sort_by(|a, b| a.total_cmp(b));
+
+    let index = grid.map(|grid| (grid, grid.cell_index()));
+    let transform = |pt: Coord| -> Result<Coord, Error> {
+        match &index {
+            Some((grid, index)) => grid
+                .get_inverse_interp_point_indexed(index, &pt)
+                .ok_or(Error::PointNotInInterpolatedGrid),
+            None => Ok(pt),
+        }
+    };
+
+    let mut rings = Vec::with_capacity(steps.len());
+    for step in steps {
+        let radius = |angle: f64| move_points_result.radius_at(step, angle.to_degrees());
+        let num_segments = match resolution {
+            RingResolution::Segments(n) => n,
+            RingResolution::MaxChordError(max_error) => {
+                let max_radius = (0..360)
+                    .map(|a| radius((a as f64).to_radians()))
+                    .fold(0., f64::max);
+                if max_error >= max_radius {
+                    8
+                } else {
+                    (PI / (1. - max_error / max_radius).acos())
+                        .ceil()
+                        .clamp(8., MAX_RING_SEGMENTS as f64) as usize
+                }
+            }
+        };
+        let ring = ring_around_point(&ref_point, radius, num_segments)
+            .0
+            .into_iter()
+            .map(transform)
+            .collect::<Result<Vec<_>, _>>()?;
+        let label_radius = radius(label_angle.to_radians());
+        let label_anchor = transform(Coord {
+            x: ref_point.x + label_radius * label_angle.to_radians().cos(),
+            y: ref_point.y + label_radius * label_angle.to_radians().sin(),
+        })?;
+        rings.push(IsochroneRing {
+            ring: ring.into(),
+            step,
+            label_anchor,
+        });
+    }
+
+    let mut annuli = Vec::with_capacity(rings.len());
+    for (k, ring) in rings.iter().enumerate() {
+        let (interiors, inner_step) = if k == 0 {
+            (vec![], 0.)
+        } else {
+            // Use the opposite orientation for the interior ring
+            let mut interior = rings[k - 1].ring.clone();
+            interior.0.reverse();
+            (vec![interior], rings[k - 1].step)
+        };
+        annuli.push(IsochroneAnnulus {
+            polygon: Polygon::new(ring.ring.clone(), interiors),
+            inner_step,
+            outer_step: ring.step,
+        });
+    }
+
+    Ok(ConcentricRings { rings, annuli })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> Vec<Coord> {
+        vec![
+            Coord { x: 0.0, y: 0.0 },
+            Coord { x: 10.0, y: 0.0 },
+            Coord { x: 0.0, y: 20.0 },
+            Coord { x: -30.0, y: 0.0 },
+        ]
+    }
+
+    #[test]
+    fn test_invalid_durations() {
+        let pts = points();
+        assert!(matches!(
+            move_points_with_options(
+                &pts,
+                &[0., 1., f64::NAN, 3.],
+                &MovePointsOptions {
+                    method: CentralTendency::Mean,
+                    unreachable_policy: UnreachablePolicy::Skip,
+                    ..Default::default()
+                },
+            ),
+            Err(Error::InvalidDuration(2))
+        ));
+        assert!(matches!(
+            move_points_with_options(
+                &pts,
+                &[0., -1., 2., 3.],
+                &MovePointsOptions {
+                    method: CentralTendency::Mean,
+                    unreachable_policy: UnreachablePolicy::Skip,
+                    ..Default::default()
+                },
+            ),
+            Err(Error::InvalidDuration(1))
+        ));
+        assert!(matches!(
+            move_points_with_options(
+                &pts,
+                &[0., 1., 0., 3.],
+                &MovePointsOptions {
+                    method: CentralTendency::Mean,
+                    unreachable_policy: UnreachablePolicy::Skip,
+                    ..Default::default()
+                },
+            ),
+            Err(Error::MultipleReferencePoints(0, 2))
+        ));
+        assert!(matches!(
+            move_points_with_options(
+                &pts,
+                &[0., f64::INFINITY, f64::INFINITY, f64::INFINITY],
+                &MovePointsOptions {
+                    unreachable_policy: UnreachablePolicy::Skip,
+                    ..Default::default()
+                },
+            ),
+            Err(Error::NoReachablePoint)
+        ));
+        for speed_model in [
+            ReferenceSpeedModel::Sectors(0),
+            ReferenceSpeedModel::Smoothed(0.),
+            ReferenceSpeedModel::Smoothed(f64::NAN),
+        ] {
+            assert!(matches!(
+                move_points_with_options(
+                    &pts,
+                    &[0., 1., 2., 3.],
+                    &MovePointsOptions {
+                        speed_model,
+                        ..Default::default()
+                    },
+                ),
+                Err(Error::InvalidReferenceSpeedModel(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_point_at_reference_point() {
+        // A point located at the reference point, but reached in a positive duration
+        let mut pts = points();
+        pts.push(Coord { x: 0.0, y: 0.0 });
+        let res = move_points(&pts, &[0., 1., 2., 3., 4.], 1., CentralTendency::Median).unwrap();
+        assert_eq!(res.points.len(), 5);
+        assert_eq!(res.points[4], Coord { x: 0.0, y: 0.0 });
+        assert!(res
+            .points
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite()));
+    }
+
+    #[test]
+    fn test_invalid_duration_mapping() {
+        let pts = points();
+        for duration_mapping in [
+            DurationMapping::Power {
+                exponent: 0.5,
+                pivot: 0.,
+            },
+            DurationMapping::Power {
+                exponent: f64::NAN,
+                pivot: 1.,
+            },
+            DurationMapping::Logarithmic { pivot: -1. },
+            DurationMapping::PiecewiseLinear(vec![]),
+            DurationMapping::PiecewiseLinear(vec![(20., 15.), (10., 10.)]),
+            DurationMapping::PiecewiseLinear(vec![(10., 10.), (10., 15.)]),
+            DurationMapping::PiecewiseLinear(vec![(10., f64::NAN)]),
+            DurationMapping::PiecewiseLinear(vec![(10., -5.)]),
+            DurationMapping::PiecewiseLinear(vec![(10., 10.), (20., 5.)]),
+            // The mapped durations must be finite, non-negative and positive for t > 0
+            DurationMapping::Custom(Arc::new(|t: f64| -t)),
+            DurationMapping::Custom(Arc::new(|_: f64| 0.)),
+            DurationMapping::Custom(Arc::new(|t: f64| t.ln())),
+        ] {
+            assert!(matches!(
+                move_points_with_options(
+                    &pts,
+                    &[0., 1., 2., 3.],
+                    &MovePointsOptions {
+                        duration_mapping,
+                        ..Default::default()
+                    },
+                ),
+                Err(Error::InvalidDurationMapping(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unreachable_policy() {
+        let pts = points();
+        let durations = [0., 1., 2., f64::INFINITY];
+
+        let res = move_points_with_options(
+            &pts,
+            &durations,
+            &MovePointsOptions {
+                method: CentralTendency::Mean,
+                unreachable_policy: UnreachablePolicy::Skip,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.unreachable, vec![3]);
+        assert_eq!(res.points.len(), 3);
+        assert_eq!(res.retain_source_points(&pts), pts[..3].to_vec());
+        assert_eq!(res.reference_speed, 10.0);
+
+        let res = move_points_with_options(
+            &pts,
+            &durations,
+            &MovePointsOptions {
+                method: CentralTendency::Mean,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.points.len(), 4);
+        assert_eq!(res.points[3], pts[3]);
+
+        let res = move_points_with_options(
+            &pts,
+            &durations,
+            &MovePointsOptions {
+                method: CentralTendency::Mean,
+                unreachable_policy: UnreachablePolicy::MaxRadius(50.),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.points[3], Coord { x: -50.0, y: 0.0 });
+        assert_eq!(res.retain_source_points(&pts), pts);
+    }
+
+    #[test]
+    fn test_directional_speeds() {
+        let pts = points();
+        let res = move_points_with_options(
+            &pts,
+            &[0., 1., 2., 1.],
+            &MovePointsOptions {
+                method: CentralTendency::Mean,
+                speed_model: ReferenceSpeedModel::Sectors(2),
+                unreachable_policy: UnreachablePolicy::Skip,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let speeds = res.directional_speeds.as_ref().unwrap();
+        assert_eq!(speeds.sector_speeds(), Some(&[10., 30.][..]));
+        assert_eq!(res.reference_speed_at(270.), 30.);
+        // Each point is reached at the reference speed of its sector
+        for (moved, source) in res.points.iter().zip(pts.iter()) {
+            assert!((moved.x - source.x).abs() < 1e-9 && (moved.y - source.y).abs() < 1e-9);
+        }
+
+        let circles = concentric_circles(&res, vec![1.]);
+        match &circles[0].0 {
+            geo_types::Geometry::LineString(ls) => {
+                assert_eq!(ls.0[0], Coord { x: 10., y: 0. });
+                assert!((ls.0[50].x + 30.).abs() < 1e-9);
+            }
+            _ => panic!("Expected a LineString"),
+        }
+    }
+
+    #[test]
+    fn test_move_points_from_origins() {
+        let pts = points();
+        let origins = [Coord { x: 0.0, y: 0.0 }, Coord { x: -40.0, y: 0.0 }];
+        let durations = vec![vec![0., 1., 2., 3.], vec![4., 5., f64::INFINITY, 1.]];
+        let options = MovePointsOptions {
+            method: CentralTendency::Mean,
+            unreachable_policy: UnreachablePolicy::Skip,
+            ..Default::default()
+        };
+        let res =
+            move_points_from_origins(&pts, &origins, &durations, OriginsRule::Nearest, &options)
+                .unwrap();
+        assert_eq!(res.nearest_origin, vec![Some(0), Some(0), Some(0), Some(1)]);
+        assert_eq!(res.reference_speed, 10.0);
+        assert_eq!(res.points[0], pts[0]);
+        assert_eq!(res.points[3], Coord { x: -30.0, y: 0.0 });
+        assert!(res.unreachable.is_empty());
+
+        // The durations are mapped before computing the speeds
+        let res = move_points_from_origins(
+            &pts,
+            &origins,
+            &durations,
+            OriginsRule::Nearest,
+            &MovePointsOptions {
+                duration_mapping: DurationMapping::Custom(Arc::new(|t: f64| 2. * t)),
+                ..options.clone()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.reference_speed, 5.0);
+        assert_eq!(res.points[3], Coord { x: -30.0, y: 0.0 });
+
+        // The reference speeds depend on the direction of travel from the origins
+        let res = move_points_from_origins(
+            &pts,
+            &origins,
+            &[vec![0., 1., 1., 3.], vec![4., 5., 6., 1.]],
+            OriginsRule::Nearest,
+            &MovePointsOptions {
+                speed_model: ReferenceSpeedModel::Sectors(4),
+                ..options.clone()
+            },
+        )
+        .unwrap();
+        assert!(res.directional_speeds.is_some());
+        // Each point is reached at the reference speed of its direction
+        for (moved, source) in res.points.iter().zip(pts.iter()) {
+            assert!((moved.x - source.x).abs() < 1e-9 && (moved.y - source.y).abs() < 1e-9);
+        }
+
+        // The mapped durations are checked
+        assert!(matches!(
+            move_points_from_origins(
+                &pts,
+                &origins,
+                &durations,
+                OriginsRule::Nearest,
+                &MovePointsOptions {
+                    duration_mapping: DurationMapping::Custom(Arc::new(|t: f64| -t)),
+                    ..options.clone()
+                },
+            ),
+            Err(Error::InvalidDurationMapping(_))
+        ));
+
+        // No point can be reached
+        assert!(matches!(
+            move_points_from_origins(
+                &pts,
+                &origins,
+                &[vec![f64::INFINITY; 4], vec![f64::INFINITY; 4]],
+                OriginsRule::Weighted(1.),
+                &options,
+            ),
+            Err(Error::NoReachablePoint)
+        ));
+
+        // A point located at an origin (but reached in a positive duration) isn't moved
+        let res = move_points_from_origins(
+            &pts,
+            &[Coord { x: 10.0, y: 0.0 }, Coord { x: -40.0, y: 0.0 }],
+            &[vec![1., 2., 3., 4.], vec![4., 5., 6., 1.]],
+            OriginsRule::Weighted(1.),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(res.points[1], pts[1]);
+        assert!(res
+            .points
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite()));
+
+        // Invalid inputs
+        assert!(matches!(
+            move_points_from_origins(&pts, &[], &[], OriginsRule::Nearest, &options),
+            Err(Error::NoOrigin)
+        ));
+        for power in [-1., f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                move_points_from_origins(
+                    &pts,
+                    &origins,
+                    &durations,
+                    OriginsRule::Weighted(power),
+                    &options,
+                ),
+                Err(Error::InvalidOriginsRule(_))
+            ));
+        }
+        assert!(matches!(
+            move_points_from_origins(
+                &pts,
+                &origins,
+                &[vec![0., 1., 2., 3.], vec![4., f64::NAN, 6., 1.]],
+                OriginsRule::Nearest,
+                &options,
+            ),
+            Err(Error::InvalidOriginDuration(1, 1))
+        ));
+    }
+
+    #[test]
+    fn test_duration_mapping() {
+        let mapping = DurationMapping::PiecewiseLinear(vec![(10., 10.), (20., 15.)]);
+        assert_eq!(mapping.apply(5.), 5.);
+        assert_eq!(mapping.apply(15.), 12.5);
+        assert_eq!(mapping.apply(30.), 20.);
+        let mapping = DurationMapping::Power {
+            exponent: 0.5,
+            pivot: 4.,
+        };
+        assert_eq!(mapping.apply(16.), 8.);
+
+        let pts = points();
+        let res = move_points_with_options(
+            &pts,
+            &[0., 1., 4., 9.],
+            &MovePointsOptions {
+                duration_mapping: DurationMapping::Custom(Arc::new(|t: f64| t.sqrt())),
+                unreachable_policy: UnreachablePolicy::Skip,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.reference_speed, 10.);
+        assert_eq!(res.points[2], Coord { x: 0., y: 20. });
+        assert_eq!(res.radius_at(9., 0.), 30.);
+    }
+
+    #[test]
+    fn test_concentric_rings() {
+        let pts = points();
+        let res = move_points_with_options(
+            &pts,
+            &[0., 1., 2., 3.],
+            &MovePointsOptions {
+                method: CentralTendency::Mean,
+                unreachable_policy: UnreachablePolicy::Skip,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let rings = concentric_rings(
+            &res,
+            &[2., 1.],
+            RingResolution::MaxChordError(0.1),
+            90.,
+            None,
+        )
+        .unwrap();
+        assert_eq!(rings.rings.len(), 2);
+        assert_eq!(rings.rings[0].step, 1.);
+        let anchor = rings.rings[1].label_anchor;
+        assert!(anchor.x.abs() < 1e-9 && (anchor.y - 2. * res.reference_speed).abs() < 1e-9);
+        // The sagitta of each segment is lower than the maximum chord error
+        let n = rings.rings[1].ring.0.len() - 1;
+        let r = 2. * res.reference_speed;
+        assert!(r * (1. - (PI / n as f64).cos()) <= 0.1);
+        assert_eq!(rings.annuli[0].polygon.interiors().len(), 0);
+        assert_eq!(rings.annuli[1].polygon.interiors().len(), 1);
+        assert_eq!(rings.annuli[1].inner_step, 1.);
+
+        // The number of segments is capped for a tiny chord error
+        let rings =
+            concentric_rings(&res, &[1.], RingResolution::MaxChordError(1e-300), 0., None).unwrap();
+        assert_eq!(rings.rings[0].ring.0.len(), MAX_RING_SEGMENTS + 1);
+
+        for resolution in [
+            RingResolution::Segments(2),
+            RingResolution::MaxChordError(0.),
+            RingResolution::MaxChordError(-1.),
+            RingResolution::MaxChordError(f64::NAN),
+        ] {
+            assert!(matches!(
+                concentric_rings(&res, &[1.], resolution, 0., None),
+                Err(Error::InvalidRingResolution(_))
+            ));
+        }
+        for step in [f64::NAN, 0., -1., f64::INFINITY] {
+            assert!(matches!(
+                concentric_rings(&res, &[1., step], RingResolution::Segments(16), 0., None),
+                Err(Error::InvalidRingStep(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_concentric_rings_through_grid() {
+        let pts = points();
+        let res = move_points(&pts, &[0., 1., 2., 3.], 1., CentralTendency::Mean).unwrap();
+        // The points aren't moved, so the grid isn't deformed
+        let grid = Grid::builder(&pts, &res.points)
+            .resolution(5.)
+            .padding(20.)
+            .build()
+            .unwrap();
+        let resolution = RingResolution::Segments(32);
+        let rings = concentric_rings(&res, &[1., 1.5], resolution, 45., None).unwrap();
+        let deformed = concentric_rings(&res, &[1., 1.5], resolution, 45., Some(&grid)).unwrap();
+        for (ring, deformed) in rings.rings.iter().zip(deformed.rings.iter()) {
+            for (p, q) in ring.ring.0.iter().zip(deformed.ring.0.iter()) {
+                assert!((p.x - q.x).abs() < 1e-6 && (p.y - q.y).abs() < 1e-6);
+            }
+            let (p, q) = (ring.label_anchor, deformed.label_anchor);
+            assert!((p.x - q.x).abs() < 1e-6 && (p.y - q.y).abs() < 1e-6);
+        }
+        // The circle doesn't fall inside the grid
+        assert!(matches!(
+            concentric_rings(&res, &[10.], resolution, 45., Some(&grid)),
+            Err(Error::PointNotInInterpolatedGrid)
+        ));
+    }
+}
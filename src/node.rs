@@ -1,4 +1,5 @@
 use crate::bbox::BBox;
+use crate::errors::Error;
 use crate::rectangle::Rectangle2D;
 use geo_types::Coord;
 use std::fmt::Debug;
@@ -30,14 +31,35 @@ impl Node {
     }
 }
 
+/// How the size of the cells of the grid is determined.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CellSize {
+    /// The size is deduced from the precision and the number of points
+    /// (see [`Grid::new`](crate::Grid::new)).
+    Precision(f64),
+    /// The size of the cells, in the unit of the coordinates.
+    Resolution(f64),
+    /// The size is deduced from the (approximate) number of cells
+    /// covering the zone.
+    CellCount(usize),
+    /// The width and the height of the cells, in the unit of the coordinates.
+    ResolutionXY(f64, f64),
+}
+
+/// The maximum number of nodes of a grid (to avoid exhausting the memory
+/// when the cells are much smaller than the zone covered by the grid).
+pub(crate) const MAX_NODES: usize = 10_000_000;
+
 /// The internal representation of the grid.
 pub(crate) struct NodeSet {
     /// The nodes of the grid
     pub nodes: Vec<Node>,
     /// Envelope of the grid
     pub zone: Rectangle2D,
-    /// Size of the cell's grid
-    pub resolution: f64,
+    /// Width of the cell's grid
+    pub resolution_x: f64,
+    /// Height of the cell's grid
+    pub resolution_y: f64,
     /// Number of nodes in X
     pub width: usize,
     /// Number of nodes in Y
@@ -45,27 +67,65 @@ pub(crate) struct NodeSet {
 }
 
 impl NodeSet {
-    pub fn new(points: &[Coord], precision: f64, bbox: Option<BBox>) -> NodeSet {
-        let mut zone = if bbox.is_none() {
-            // Compute the rectangle from the given points
-            Rectangle2D::from_points(points)
-        } else {
+    pub fn new(
+        points: &[Coord],
+        cell_size: CellSize,
+        bbox: Option<BBox>,
+        padding: f64,
+    ) -> Result<NodeSet, Error> {
+        let mut zone = if let Some(bbox) = bbox {
             // Use the given bounding box to create the rectangle
-            let mut r = Rectangle2D::from_bbox(&bbox.unwrap());
+            let mut r = Rectangle2D::from_bbox(&bbox);
             // And extend it to include all source points if necessary
             for p in points {
                 r.add(p);
             }
             r
+        } else {
+            // Compute the rectangle from the given points
+            Rectangle2D::from_points(points)
         };
-        let resolution =
-            1. / precision * (zone.width() * zone.height() / points.len() as f64).sqrt();
+        if padding > 0. {
+            zone.set_rect_from_center(
+                &Coord {
+                    x: zone.center_x(),
+                    y: zone.center_y(),
+                },
+                &Coord {
+                    x: zone.min_x() - padding,
+                    y: zone.min_y() - padding,
+                },
+            );
+        }
+        if !(zone.width() > 0. && zone.height() > 0.) {
+            return Err(Error::DegenerateBBox);
+        }
+        let (resolution_x, resolution_y) = match cell_size {
+            CellSize::Precision(precision) => {
+                let r =
+                    1. / precision * (zone.width() * zone.height() / points.len() as f64).sqrt();
+                (r, r)
+            }
+            CellSize::Resolution(resolution) => (resolution, resolution),
+            CellSize::CellCount(n_cells) => {
+                let r = (zone.width() * zone.height() / n_cells as f64).sqrt();
+                (r, r)
+            }
+            CellSize::ResolutionXY(resolution_x, resolution_y) => (resolution_x, resolution_y),
+        };
+
+        // Number of nodes in X and in Y (once the zone is extended below)
+        let n_x = (zone.width() / resolution_x).ceil() + 2.;
+        let n_y = (zone.height() / resolution_y).ceil() + 2.;
+        if n_x * n_y > MAX_NODES as f64 {
+            return Err(Error::TooManyNodes((n_x * n_y) as usize, MAX_NODES));
+        }
 
-        let mut width = (zone.width() / resolution).ceil() as usize + 1;
-        let mut height = (zone.height() / resolution).ceil() as usize + 1;
+        let mut width = (zone.width() / resolution_x).ceil() as usize + 1;
+        let mut height = (zone.height() / resolution_y).ceil() as usize + 1;
 
-        let dx = width as f64 * resolution - zone.width();
-        let dy = height as f64 * resolution - zone.height();
+        let dx = width as f64 * resolution_x - zone.width();
+        let dy = height as f64 * resolution_y - zone.height();
 
         zone.set_rect_from_center(
             &Coord {
@@ -91,20 +151,21 @@ impl NodeSet {
                     i,
                     j,
                     Coord {
-                        x: min_x + j as f64 * resolution,
-                        y: max_y - i as f64 * resolution,
+                        x: min_x + j as f64 * resolution_x,
+                        y: max_y - i as f64 * resolution_y,
                     },
                 ));
             }
         }
 
-        NodeSet {
+        Ok(NodeSet {
             nodes,
             zone,
-            resolution,
+            resolution_x,
+            resolution_y,
             width,
             height,
-        }
+        })
     }
 
     #[inline]
@@ -120,14 +181,16 @@ impl NodeSet {
         &mut self.nodes[i * self.width + j]
     }
 
+    // The indexes are clamped so that the points on the bottom and right
+    // edges of the zone belong to the last cell
     #[inline]
     fn get_i(&self, p: &Coord) -> usize {
-        ((self.zone.max_y() - p.y) / self.resolution).floor() as usize
+        (((self.zone.max_y() - p.y) / self.resolution_y).floor() as usize).min(self.height - 2)
     }
 
     #[inline]
     fn get_j(&self, p: &Coord) -> usize {
-        ((p.x - self.zone.min_x()) / self.resolution).floor() as usize
+        (((p.x - self.zone.min_x()) / self.resolution_x).floor() as usize).min(self.width - 2)
     }
 
     pub fn get_adjacent_nodes(&self, point: &Coord) -> [Node; 4] {
@@ -216,7 +279,7 @@ impl NodeSet {
                 sy += n.y;
                 nb += 1;
             } else {
-                sy += self.resolution * scale_y;
+                sy += self.resolution_y * scale_y;
             }
             if j > 0 {
                 let n = &self.get_node(i, j - 1).interp;
@@ -224,7 +287,7 @@ impl NodeSet {
                 sy += n.y;
                 nb += 1;
             } else {
-                sx -= self.resolution * scale_x;
+                sx -= self.resolution_x * scale_x;
             }
             if i < self.height - 1 {
                 let n = &self.get_node(i + 1, j).interp;
@@ -232,7 +295,7 @@ impl NodeSet {
                 sy += n.y;
                 nb += 1;
             } else {
-                sy -= self.resolution * scale_y;
+                sy -= self.resolution_y * scale_y;
             }
             if j < self.width - 1 {
                 let n = &self.get_node(i, j + 1).interp;
@@ -240,7 +303,7 @@ impl NodeSet {
                 sy += n.y;
                 nb += 1;
             } else {
-                sx += self.resolution * scale_x;
+                sx += self.resolution_x * scale_x;
             }
             Coord {
                 x: sx / nb as f64,
@@ -254,7 +317,8 @@ impl Debug for NodeSet {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("NodeSet")
             .field("zone", &self.zone)
-            .field("resolution", &self.resolution)
+            .field("resolution_x", &self.resolution_x)
+            .field("resolution_y", &self.resolution_y)
             .field("width", &self.width)
             .field("height", &self.height)
             .finish()
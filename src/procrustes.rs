@@ -142,9 +142,11 @@ pub fn procrustes(points1: &[Coord], points2: &[Coord]) -> Result<ProcrustesResu
     let rotated2_flipped = rotate_points(&scaled2, -angle);
 
     // Reflect the second set of points across the y-axis
+    // (the optimal rotation angle of the reflected points differs from the one above)
     let reflected2 = reflect_points(&scaled2);
-    let reflected2_rotated = rotate_points(&reflected2, angle);
-    let reflected2_rotated_flipped = rotate_points(&reflected2, -angle);
+    let angle_reflected = optimal_rotation(&scaled1, &reflected2);
+    let reflected2_rotated = rotate_points(&reflected2, angle_reflected);
+    let reflected2_rotated_flipped = rotate_points(&reflected2, -angle_reflected);
 
     // Compute the error (aka the Procrustes distance,
     // cf. https://en.wikipedia.org/wiki/Procrustes_analysis#Shape_comparison)
@@ -167,7 +169,12 @@ pub fn procrustes(points1: &[Coord], points2: &[Coord]) -> Result<ProcrustesResu
         && error_reflected < error_flipped
         && error_reflected < error_reflected_flipped
     {
-        (reflected2_rotated, error_reflected.sqrt(), true, angle)
+        (
+            reflected2_rotated,
+            error_reflected.sqrt(),
+            true,
+            angle_reflected,
+        )
     } else if error_reflected_flipped < error_original
         && error_reflected_flipped < error_flipped
         && error_reflected_flipped < error_reflected
@@ -176,7 +183,7 @@ pub fn procrustes(points1: &[Coord], points2: &[Coord]) -> Result<ProcrustesResu
             reflected2_rotated_flipped,
             error_reflected_flipped.sqrt(),
             true,
-            -angle,
+            -angle_reflected,
         )
     } else {
         (rotated2, error_original.sqrt(), false, angle)
@@ -204,3 +211,38 @@ pub fn procrustes(points1: &[Coord], points2: &[Coord]) -> Result<ProcrustesResu
         },
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_procrustes_reflected_configuration() {
+        let points1 = vec![
+            Coord { x: 0., y: 0. },
+            Coord { x: 10., y: 0. },
+            Coord { x: 10., y: 5. },
+            Coord { x: 3., y: 8. },
+            Coord { x: -2., y: 4. },
+        ];
+        // Reflect, rotate (by 0.7 rad), scale and translate the points
+        let (angle, scale): (f64, f64) = (0.7, 0.25);
+        let points2 = points1
+            .iter()
+            .map(|p| {
+                let (x, y) = (-p.x, p.y);
+                Coord {
+                    x: scale * (x * angle.cos() - y * angle.sin()) + 100.,
+                    y: scale * (x * angle.sin() + y * angle.cos()) - 50.,
+                }
+            })
+            .collect::<Vec<_>>();
+        let result = procrustes(&points1, &points2).unwrap();
+        assert!(result.reflection);
+        assert!(result.error < 1e-9);
+        assert!((result.scale - 1. / scale).abs() < 1e-9);
+        for (p, q) in result.points.iter().zip(points1.iter()) {
+            assert!((p.x - q.x).abs() < 1e-9 && (p.y - q.y).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,545 @@
+use crate::errors::Error;
+use crate::grid::Grid;
+use geo_types::Coord;
+use std::io::Write;
+
+/// The value written for the missing (NaN) values of a raster without
+/// nodata value in an ESRI ASCII Grid (where a nodata value is needed).
+const DEFAULT_NODATA: f64 = -9999.;
+
+/// A raster, stored in memory as a buffer of pixel values.
+///
+/// The pixels are stored band after band, each band being stored row after row
+/// (from the top row) and, in each row, from left to right.
+/// The position of the pixels is given by the geotransform (using the convention of
+/// GDAL): the coordinates of the top left corner of the pixel at (row, column) are
+/// `x = gt[0] + column * gt[1] + row * gt[2]` and `y = gt[3] + column * gt[4] + row * gt[5]`
+/// (`gt[5]` being negative for a north-up raster).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    /// The number of columns.
+    pub width: usize,
+    /// The number of rows.
+    pub height: usize,
+    /// The number of bands.
+    pub n_bands: usize,
+    /// The geotransform of the raster.
+    pub geotransform: [f64; 6],
+    /// The value of the missing pixels, if any (NaN values are also considered missing).
+    pub nodata: Option<f64>,
+    /// The pixel values (`n_bands * height * width` values).
+    pub data: Vec<f64>,
+}
+
+/// A value computed at each node of the grid (see [`Grid::node_raster`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeField {
+    /// The displacement of the node along the x axis (interpolated x - source x).
+    DisplacementX,
+    /// The displacement of the node along the y axis (interpolated y - source y).
+    DisplacementY,
+    /// The magnitude of the displacement of the node.
+    DisplacementMagnitude,
+    /// The deformation strength of the node (see [`Grid::node_deformation_strength`]).
+    DeformationStrength,
+}
+
+impl Raster {
+    /// Create a raster from its pixel values (see [`Raster`] for their layout).
+    pub fn new(
+        width: usize,
+        height: usize,
+        n_bands: usize,
+        geotransform: [f64; 6],
+        nodata: Option<f64>,
+        data: Vec<f64>,
+    ) -> Result<Raster, Error> {
+        let raster = Raster {
+            width,
+            height,
+            n_bands,
+            geotransform,
+            nodata,
+            data,
+        };
+        raster.check()?;
+        Ok(raster)
+    }
+
+    /// Retrieve the value of a pixel.
+    pub fn get(&self, band: usize, row: usize, column: usize) -> f64 {
+        self.data[(band * self.height + row) * self.width + column]
+    }
+
+    /// Retrieve the values of a band.
+    pub fn band(&self, band: usize) -> &[f64] {
+        let size = self.width * self.height;
+        &self.data[band * size..(band + 1) * size]
+    }
+
+    /// Whether the value is a missing value (NaN or the nodata value).
+    pub fn is_nodata(&self, value: f64) -> bool {
+        value.is_nan() || self.nodata == Some(value)
+    }
+
+    /// Compute the coordinates of the position given in pixels
+    /// (`(0, 0)` being the top left corner of the raster and `(0.5, 0.5)`
+    /// the center of its top left pixel).
+    pub fn pixel_to_coord(&self, row: f64, column: f64) -> Coord {
+        let gt = &self.geotransform;
+        Coord {
+            x: gt[0] + column * gt[1] + row * gt[2],
+            y: gt[3] + column * gt[4] + row * gt[5],
+        }
+    }
+
+    /// Compute the position in pixels (row, column) of the given coordinates
+    /// (the inverse of [`Raster::pixel_to_coord`]).
+    pub fn coord_to_pixel(&self, coord: &Coord) -> (f64, f64) {
+        let gt = &self.geotransform;
+        let det = gt[1] * gt[5] - gt[2] * gt[4];
+        let (dx, dy) = (coord.x - gt[0], coord.y - gt[3]);
+        let column = (gt[5] * dx - gt[2] * dy) / det;
+        let row = (gt[1] * dy - gt[4] * dx) / det;
+        (row, column)
+    }
+
+    /// Check the consistency of the dimensions, of the buffer and of the geotransform.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        let gt = &self.geotransform;
+        if self.width == 0
+            || self.height == 0
+            || self.n_bands == 0
+            || self.data.len() != self.n_bands * self.height * self.width
+            || !gt.iter().all(|v| v.is_finite())
+            || gt[1] * gt[5] - gt[2] * gt[4] == 0.
+        {
+            return Err(Error::InvalidRaster);
+        }
+        Ok(())
+    }
+
+    /// Write a band of the raster in the ESRI ASCII Grid format.
+    ///
+    /// The raster must not be rotated and, if its pixels are not square, the
+    /// `dx` and `dy` header lines (supported by GDAL) are written instead of `cellsize`.
+    /// The missing values are written as the nodata value (-9999 if the raster
+    /// doesn't have one).
+    pub fn write_ascii_grid<W: Write>(&self, writer: &mut W, band: usize) -> Result<(), Error> {
+        self.check()?;
+        if band >= self.n_bands {
+            return Err(Error::InvalidBand(band));
+        }
+        let gt = &self.geotransform;
+        if gt[2] != 0. || gt[4] != 0. {
+            return Err(Error::RotatedRaster);
+        }
+        let nodata = self.nodata.unwrap_or(DEFAULT_NODATA);
+        let (size_x, size_y) = (gt[1].abs(), gt[5].abs());
+        let mut s = format!("ncols {}\nnrows {}\n", self.width, self.height);
+        s.push_str(&format!(
+            "xllcorner {}\nyllcorner {}\n",
+            gt[0].min(gt[0] + self.width as f64 * gt[1]),
+            gt[3].min(gt[3] + self.height as f64 * gt[5]),
+        ));
+        if size_x == size_y {
+            s.push_str(&format!("cellsize {}\n", size_x));
+        } else {
+            s.push_str(&format!("dx {}\ndy {}\n", size_x, size_y));
+        }
+        s.push_str(&format!("NODATA_value {}\n", nodata));
+        // The rows are written from the top (north) to the bottom (south)
+        let rows: Vec<usize> = if gt[5] < 0. {
+            (0..self.height).collect()
+        } else {
+            (0..self.height).rev().collect()
+        };
+        for row in rows {
+            let mut columns: Vec<usize> = (0..self.width).collect();
+            if gt[1] < 0. {
+                columns.reverse();
+            }
+            let line = columns
+                .into_iter()
+                .map(|column| {
+                    let value = self.get(band, row, column);
+                    if self.is_nodata(value) {
+                        format!("{}", nodata)
+                    } else {
+                        format!("{}", value)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            s.push_str(&line);
+            s.push('\n');
+        }
+        writer
+            .write_all(s.as_bytes())
+            .map_err(|e| Error::RasterWrite(e.to_string()))
+    }
+
+    /// Write the raster in the GeoTIFF format (uncompressed, with all the bands
+    /// stored as 64-bit floating point samples).
+    ///
+    /// As this crate doesn't handle projections, the EPSG code of the (projected)
+    /// CRS of the raster can be given to be stored in the file.
+    /// The nodata value, if any, is stored in the `GDAL_NODATA` tag.
+    /// As in the ESRI ASCII Grid format, the rows and columns of a raster that
+    /// is not rotated are reordered if needed so that the file is north-up.
+    ///
+    /// An error is returned if the raster has more than 65535 bands or doesn't
+    /// fit in a (non BigTIFF) GeoTIFF file.
+    pub fn write_geotiff<W: Write>(&self, writer: &mut W, epsg: Option<u16>) -> Result<(), Error> {
+        self.check()?;
+        let n_bands = u16::try_from(self.n_bands).map_err(|_| {
+            Error::RasterWrite("a GeoTIFF file can't store more than 65535 bands".to_string())
+        })?;
+        let too_large = || {
+            Error::RasterWrite(
+                "the raster is too large for a (non BigTIFF) GeoTIFF file".to_string(),
+            )
+        };
+        // The header and the pixels must be addressable with 32-bit offsets
+        let byte_count = self
+            .data
+            .len()
+            .checked_mul(8)
+            .filter(|&n| n <= u32::MAX as usize - 8)
+            .ok_or_else(too_large)?;
+        let byte_count = u32::try_from(byte_count).map_err(|_| too_large())?;
+        let width = u32::try_from(self.width).map_err(|_| too_large())?;
+        let height = u32::try_from(self.height).map_err(|_| too_large())?;
+        let gt = &self.geotransform;
+        // Without rotation, the pixels are written from the top left corner of
+        // the raster (the rows and columns being reversed if needed), so that
+        // the pixel scales are positive
+        let unrotated = gt[2] == 0. && gt[4] == 0.;
+        let (flip_x, flip_y) = (unrotated && gt[1] < 0., unrotated && gt[5] > 0.);
+        let mut entries = vec![
+            (256, TagValue::Long(vec![width])),
+            (257, TagValue::Long(vec![height])),
+            (258, TagValue::Short(vec![64; self.n_bands])),
+            // No compression
+            (259, TagValue::Short(vec![1])),
+            // BlackIsZero
+            (262, TagValue::Short(vec![1])),
+            // StripOffsets (the pixels are written just after the header)
+            (273, TagValue::Long(vec![8])),
+            (277, TagValue::Short(vec![n_bands])),
+            (278, TagValue::Long(vec![height])),
+            (279, TagValue::Long(vec![byte_count])),
+            // Chunky (interleaved) bands
+            (284, TagValue::Short(vec![1])),
+        ];
+        if n_bands > 1 {
+            // The additional bands are unspecified data
+            entries.push((338, TagValue::Short(vec![0; self.n_bands - 1])));
+        }
+        // Floating point samples
+        entries.push((339, TagValue::Short(vec![3; self.n_bands])));
+        if unrotated {
+            let left = gt[0].min(gt[0] + self.width as f64 * gt[1]);
+            let top = gt[3].max(gt[3] + self.height as f64 * gt[5]);
+            entries.push((33550, TagValue::Double(vec![gt[1].abs(), gt[5].abs(), 0.])));
+            entries.push((33922, TagValue::Double(vec![0., 0., 0., left, top, 0.])));
+        } else {
+            entries.push((
+                34264,
+                TagValue::Double(vec![
+                    gt[1], gt[2], 0., gt[0], gt[4], gt[5], 0., gt[3], 0., 0., 0., 0., 0., 0., 0.,
+                    1.,
+                ]),
+            ));
+        }
+        // GeoKeyDirectory: RasterPixelIsArea and, if known, the projected CRS
+        let mut geokeys = vec![1, 1, 0, 1, 1025, 0, 1, 1];
+        if let Some(epsg) = epsg {
+            geokeys[3] = 3;
+            geokeys.splice(4..4, [1024, 0, 1, 1]);
+            geokeys.extend([3072, 0, 1, epsg]);
+        }
+        entries.push((34735, TagValue::Short(geokeys)));
+        if let Some(nodata) = self.nodata {
+            entries.push((42113, TagValue::Ascii(format!("{}", nodata))));
+        }
+
+        // Header, pixels, values of the tags that don't fit in the entries, then the IFD
+        let mut buffer = Vec::with_capacity(8 + self.data.len() * 8 + 1024);
+        buffer.extend_from_slice(b"II");
+        buffer.extend_from_slice(&42u16.to_le_bytes());
+        buffer.extend_from_slice(&0u32.to_le_bytes());
+        for i in 0..self.height {
+            let row = if flip_y { self.height - 1 - i } else { i };
+            for j in 0..self.width {
+                let column = if flip_x { self.width - 1 - j } else { j };
+                for band in 0..self.n_bands {
+                    buffer.extend_from_slice(&self.get(band, row, column).to_le_bytes());
+                }
+            }
+        }
+        let mut ifd = Vec::with_capacity(2 + entries.len() * 12 + 4);
+        ifd.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for (tag, value) in entries {
+            let (field_type, count, bytes) = value.encode();
+            ifd.extend_from_slice(&(tag as u16).to_le_bytes());
+            ifd.extend_from_slice(&field_type.to_le_bytes());
+            ifd.extend_from_slice(&count.to_le_bytes());
+            if bytes.len() <= 4 {
+                let mut inline = [0u8; 4];
+                inline[..bytes.len()].copy_from_slice(&bytes);
+                ifd.extend_from_slice(&inline);
+            } else {
+                if buffer.len() % 2 == 1 {
+                    buffer.push(0);
+                }
+                ifd.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+                buffer.extend_from_slice(&bytes);
+            }
+        }
+        // No other IFD
+        ifd.extend_from_slice(&0u32.to_le_bytes());
+        if buffer.len() % 2 == 1 {
+            buffer.push(0);
+        }
+        let ifd_offset = u32::try_from(buffer.len()).map_err(|_| too_large())?;
+        if buffer.len() + ifd.len() > u32::MAX as usize {
+            return Err(too_large());
+        }
+        buffer[4..8].copy_from_slice(&ifd_offset.to_le_bytes());
+        buffer.extend_from_slice(&ifd);
+        writer
+            .write_all(&buffer)
+            .map_err(|e| Error::RasterWrite(e.to_string()))
+    }
+}
+
+/// The value of a TIFF tag.
+enum TagValue {
+    Short(Vec<u16>),
+    Long(Vec<u32>),
+    Double(Vec<f64>),
+    Ascii(String),
+}
+
+impl TagValue {
+    /// The TIFF field type, the number of values and the (little-endian) bytes of the value.
+    fn encode(&self) -> (u16, u32, Vec<u8>) {
+        match self {
+            TagValue::Short(v) => (
+                3,
+                v.len() as u32,
+                v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            ),
+            TagValue::Long(v) => (
+                4,
+                v.len() as u32,
+                v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            ),
+            TagValue::Double(v) => (
+                12,
+                v.len() as u32,
+                v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            ),
+            TagValue::Ascii(s) => {
+                let mut bytes = s.as_bytes().to_vec();
+                bytes.push(0);
+                (2, bytes.len() as u32, bytes)
+            }
+        }
+    }
+}
+
+impl Grid {
+    /// Export values computed at each node of the grid as a raster
+    /// (with one band per field, in the given order), for use in GIS software
+    /// (see [`Raster::write_ascii_grid`] and [`Raster::write_geotiff`]).
+    ///
+    /// Each pixel of the raster is centered on a node of the (source) grid,
+    /// so the raster has the dimensions of the grid (see [`Grid::grid_dimensions`])
+    /// and its pixels have the size of the cells of the grid.
+    ///
+    /// An error is returned if no field is given.
+    pub fn node_raster(&self, fields: &[NodeField]) -> Result<Raster, Error> {
+        let (width, height) = self.grid_dimensions();
+        let (resolution_x, resolution_y) = self.resolution_xy();
+        let bbox = self.bbox();
+        let mut data = Vec::with_capacity(fields.len() * width * height);
+        for field in fields {
+            for i in 0..height {
+                for j in 0..width {
+                    let (dx, dy) = self.node_displacement(i, j);
+                    data.push(match field {
+                        NodeField::DisplacementX => dx,
+                        NodeField::DisplacementY => dy,
+                        NodeField::DisplacementMagnitude => dx.hypot(dy),
+                        NodeField::DeformationStrength => self.node_deformation_strength(i, j),
+                    });
+                }
+            }
+        }
+        Raster::new(
+            width,
+            height,
+            fields.len(),
+            [
+                bbox.xmin - resolution_x / 2.,
+                resolution_x,
+                0.,
+                bbox.ymax + resolution_y / 2.,
+                0.,
+                -resolution_y,
+            ],
+            None,
+            data,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sheared_grid;
+
+    #[test]
+    fn test_node_raster() {
+        let grid = sheared_grid();
+        let raster = grid
+            .node_raster(&[
+                NodeField::DisplacementX,
+                NodeField::DisplacementY,
+                NodeField::DisplacementMagnitude,
+            ])
+            .unwrap();
+        assert_eq!((raster.width, raster.height), grid.grid_dimensions());
+        assert_eq!(raster.n_bands, 3);
+        let nodes = grid.get_grid(crate::GridType::Source);
+        let interp = grid.get_grid(crate::GridType::Interpolated);
+        // The pixel (1, 2) is centered on the top left node of the cell (1, 2)
+        let cell = (raster.width - 1) + 2;
+        let (source, image) = (nodes[cell].exterior().0[0], interp[cell].exterior().0[0]);
+        assert_eq!(raster.pixel_to_coord(1.5, 2.5), source);
+        assert_eq!(raster.coord_to_pixel(&source), (1.5, 2.5));
+        assert_eq!(raster.get(0, 1, 2), image.x - source.x);
+        assert_eq!(raster.get(1, 1, 2), image.y - source.y);
+        assert_eq!(
+            raster.get(2, 1, 2),
+            raster.get(0, 1, 2).hypot(raster.get(1, 1, 2))
+        );
+        assert!(matches!(grid.node_raster(&[]), Err(Error::InvalidRaster)));
+    }
+
+    #[test]
+    fn test_write_rasters() {
+        let raster = Raster::new(
+            3,
+            2,
+            1,
+            [100., 10., 0., 50., 0., -10.],
+            None,
+            vec![1., 2., 3., 4., f64::NAN, 6.],
+        )
+        .unwrap();
+        let mut ascii = Vec::new();
+        raster.write_ascii_grid(&mut ascii, 0).unwrap();
+        assert_eq!(
+            String::from_utf8(ascii).unwrap(),
+            "ncols 3\nnrows 2\nxllcorner 100\nyllcorner 30\ncellsize 10\nNODATA_value -9999\n1 2 3\n4 -9999 6\n"
+        );
+        assert!(matches!(
+            raster.write_ascii_grid(&mut Vec::new(), 1),
+            Err(Error::InvalidBand(1))
+        ));
+
+        let mut tiff = Vec::new();
+        raster.write_geotiff(&mut tiff, Some(2154)).unwrap();
+        assert_eq!(&tiff[..4], b"II\x2a\x00");
+        assert_eq!(f64::from_le_bytes(tiff[8..16].try_into().unwrap()), 1.);
+        let tags = read_ifd(&tiff);
+        assert_eq!(tags[&256], vec![3.]);
+        assert_eq!(tags[&257], vec![2.]);
+        // ModelPixelScale and ModelTiepoint (the top left corner of the raster)
+        assert_eq!(tags[&33550], vec![10., 10., 0.]);
+        assert_eq!(tags[&33922], vec![0., 0., 0., 100., 50., 0.]);
+        // GeoKeyDirectory: projected model, pixel is area and EPSG:2154
+        assert_eq!(
+            tags[&34735],
+            [1, 1, 0, 3, 1024, 0, 1, 1, 1025, 0, 1, 1, 3072, 0, 1, 2154]
+                .iter()
+                .map(|&v| v as f64)
+                .collect::<Vec<_>>()
+        );
+        assert!(!tags.contains_key(&42113));
+
+        // A south-up raster, with its columns from east to west, is written north-up
+        let flipped = Raster::new(
+            3,
+            2,
+            1,
+            [130., -10., 0., 30., 0., 10.],
+            None,
+            vec![6., 5., 4., 3., 2., 1.],
+        )
+        .unwrap();
+        let mut tiff = Vec::new();
+        flipped.write_geotiff(&mut tiff, None).unwrap();
+        let tags = read_ifd(&tiff);
+        assert_eq!(tags[&33550], vec![10., 10., 0.]);
+        assert_eq!(tags[&33922], vec![0., 0., 0., 100., 50., 0.]);
+        let pixels = tiff[8..56]
+            .chunks(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(pixels, vec![1., 2., 3., 4., 5., 6.]);
+
+        let bands = Raster::new(
+            1,
+            1,
+            65536,
+            [0., 1., 0., 0., 0., -1.],
+            None,
+            vec![0.; 65536],
+        )
+        .unwrap();
+        assert!(matches!(
+            bands.write_geotiff(&mut Vec::new(), None),
+            Err(Error::RasterWrite(_))
+        ));
+    }
+
+    /// Read the numeric values of the tags of the first IFD of a (little-endian) TIFF file.
+    fn read_ifd(tiff: &[u8]) -> std::collections::HashMap<u16, Vec<f64>> {
+        let u16_at = |k: usize| u16::from_le_bytes([tiff[k], tiff[k + 1]]);
+        let u32_at = |k: usize| u32::from_le_bytes(tiff[k..k + 4].try_into().unwrap());
+        let ifd = u32_at(4) as usize;
+        (0..u16_at(ifd) as usize)
+            .map(|e| {
+                let entry = ifd + 2 + e * 12;
+                let (field_type, count) = (u16_at(entry + 2), u32_at(entry + 4) as usize);
+                let size = match field_type {
+                    3 => 2,
+                    4 => 4,
+                    12 => 8,
+                    _ => 1,
+                };
+                let offset = if size * count <= 4 {
+                    entry + 8
+                } else {
+                    u32_at(entry + 8) as usize
+                };
+                let values = (0..count)
+                    .map(|k| {
+                        let at = offset + k * size;
+                        match field_type {
+                            3 => u16_at(at) as f64,
+                            4 => u32_at(at) as f64,
+                            12 => f64::from_le_bytes(tiff[at..at + 8].try_into().unwrap()),
+                            _ => tiff[at] as f64,
+                        }
+                    })
+                    .collect();
+                (u16_at(entry), values)
+            })
+            .collect()
+    }
+}
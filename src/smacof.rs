@@ -0,0 +1,681 @@
+use crate::errors::Error;
+use crate::moving_points_multipolar::{
+    generate_positions_from_durations, symmetrize_durations, validate_durations, Symmetrization,
+};
+use crate::procrustes::{procrustes, ProcrustesResult};
+use geo_types::Coord;
+use nalgebra::DMatrix;
+
+/// The type of Multidimensional Scaling (MDS) performed by the [`smacof`] function,
+/// i.e. how the durations are transformed into the disparities that
+/// the distances between the points have to reproduce.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MdsType {
+    /// The disparities are the durations (ratio MDS).
+    Metric,
+    /// The disparities are a linear function of the durations
+    /// (`a + b * duration`, with `a` and `b` fitted at each iteration).
+    Interval,
+    /// The disparities only have to preserve the order of the durations
+    /// (non-metric MDS, using a monotone regression at each iteration).
+    Ordinal,
+}
+
+/// The result of the [`smacof`] function.
+#[derive(Debug, Clone)]
+pub struct SmacofResult {
+    /// The positions of the points (in the order of the durations).
+    pub points: Vec<Coord>,
+    /// The final stress value (Kruskal stress-1, i.e. the square root of the
+    /// raw stress divided by the sum of the squared distances).
+    pub stress: f64,
+    /// The final raw stress value (weighted sum of the squared differences
+    /// between the disparities and the distances).
+    pub raw_stress: f64,
+    /// The number of iterations performed.
+    pub n_iter: usize,
+    /// Whether the algorithm converged (before reaching the maximum
+    /// number of iterations).
+    pub converged: bool,
+}
+
+/// Takes a duration matrix and returns the coordinates of the points obtained
+/// using the SMACOF (Scaling by MAjorizing a COmplicated Function) algorithm,
+/// which iteratively minimizes the stress between the distances of the points
+/// and the disparities derived from the durations (see [`MdsType`]).
+///
+/// Unlike the PCoA used by [`generate_positions_from_durations`], this does not
+/// assume that the durations are Euclidean distances, which generally gives
+/// a better representation of travel times.
+///
+/// The initial configuration can be provided (for example the source points,
+/// in the order of the durations), otherwise the result of the PCoA is used;
+/// its coordinates must be finite and its points must not all coincide.
+/// The algorithm stops when the relative decrease of the stress is lower
+/// than the tolerance (a finite value, positive or zero) or when the maximum
+/// number of iterations (which must be positive) is reached.
+///
+/// The duration matrix is first made symmetric using the given [`Symmetrization`]
+/// policy (note that it must be square, with a zero diagonal, without negative
+/// durations and without durations missing in both directions, otherwise an error
+/// is returned; see [`generate_positions_from_incomplete_durations`](crate::generate_positions_from_incomplete_durations)
+/// for incomplete duration matrices).
+///
+/// Note that the points are returned in the order of the input durations
+/// and are centered around (0, 0). The caller is responsible for
+/// translating/scaling/rotating the points as needed to fit them to the reference points (see [`adjustment`](crate::adjustment) or
+/// [`procrustes`](crate::procrustes) modules for this).
+pub fn smacof(
+    durations: &[Vec<f64>],
+    symmetrization: Symmetrization,
+    mds_type: MdsType,
+    init: Option<&[Coord]>,
+    tolerance: f64,
+    max_iter: usize,
+) -> Result<SmacofResult, Error> {
+    let durations = symmetrize_durations(durations, symmetrization)?;
+    validate_durations(&durations)?;
+    let n = durations.len();
+    let init = match init {
+        Some(init) => {
+            if init.len() != n {
+                return Err(Error::InvalidInitialConfigurationLength);
+            }
+            init.to_vec()
+        }
+        None => generate_positions_from_durations(durations.clone(), Symmetrization::Mean)?,
+    };
+    let delta = durations
+        .iter()
+        .flat_map(|x| x.iter().copied())
+        .collect::<Vec<_>>();
+
+    run_smacof(
+        Dissimilarities {
+            delta: &delta,
+            weights: None,
+            n,
+        },
+        mds_type,
+        init,
+        tolerance,
+        max_iter,
+        None,
+    )
+}
+
+/// The result of the [`generate_positions_anchored_to_source`] function.
+#[derive(Debug)]
+pub struct AnchoredPositions {
+    /// The positions of the points, in the CRS of the source points
+    /// (and in the order of the durations).
+    pub points: Vec<Coord>,
+    /// The final stress value of the MDS configuration (Kruskal stress-1,
+    /// computed before the penalty and the alignment are taken into account).
+    pub stress: f64,
+    /// The number of SMACOF iterations performed.
+    pub n_iter: usize,
+    /// Whether the SMACOF algorithm converged (before reaching the maximum
+    /// number of iterations).
+    pub converged: bool,
+    /// The parameters of the Procrustes alignment used to bring the MDS
+    /// configuration back to the CRS of the source points.
+    pub alignment: ProcrustesResult,
+}
+
+/// Takes the source points and the duration matrix between them and returns
+/// the positions of the points (obtained using the SMACOF algorithm, see [`smacof`])
+/// directly in the CRS of the source points, so that no separate
+/// [`adjustment`](crate::adjustment) or [`procrustes`](crate::procrustes) step is needed.
+///
+/// The MDS configuration is initialised from the geographic positions of the
+/// source points (scaled to the unit of the durations) and each point is
+/// penalised for moving away from its geographic position, with a penalty weight
+/// of `anchor_weight` (a finite value, positive or zero, relative to the weight
+/// of the durations: `0.` means no penalty, i.e. only the initial configuration
+/// depends on the source points, while large values keep the points close to
+/// their geographic positions).
+/// The configuration is then aligned on the source points using a Procrustes
+/// analysis, whose parameters are returned along with the points.
+///
+/// The duration matrix is first made symmetric using the given [`Symmetrization`]
+/// policy (note that it must be square, with a zero diagonal, without negative
+/// durations and without durations missing in both directions, and that it must have
+/// one row per source point, otherwise an error is returned).
+pub fn generate_positions_anchored_to_source(
+    source_points: &[Coord],
+    durations: &[Vec<f64>],
+    symmetrization: Symmetrization,
+    mds_type: MdsType,
+    anchor_weight: f64,
+    tolerance: f64,
+    max_iter: usize,
+) -> Result<AnchoredPositions, Error> {
+    if !(anchor_weight.is_finite() && anchor_weight >= 0.) {
+        return Err(Error::InvalidAnchorWeight(anchor_weight));
+    }
+    let durations = symmetrize_durations(durations, symmetrization)?;
+    validate_durations(&durations)?;
+    let n = durations.len();
+    if source_points.len() != n {
+        return Err(Error::InvalidSourcePointsLength);
+    }
+    let delta = durations
+        .iter()
+        .flat_map(|x| x.iter().copied())
+        .collect::<Vec<_>>();
+
+    // Center the source points and scale them to the unit of the durations
+    // (using the scale factor minimizing the stress of this configuration)
+    let center = source_points
+        .iter()
+        .fold(Coord { x: 0., y: 0. }, |acc, p| Coord {
+            x: acc.x + p.x / n as f64,
+            y: acc.y + p.y / n as f64,
+        });
+    let centered = source_points
+        .iter()
+        .map(|p| Coord {
+            x: p.x - center.x,
+            y: p.y - center.y,
+        })
+        .collect::<Vec<_>>();
+    let (sum_delta_d, sum_d_sq) = (0..n).flat_map(|i| ((i + 1)..n).map(move |j| (i, j))).fold(
+        (0., 0.),
+        |(sdd, sdsq), (i, j)| {
+            let d = ((centered[i].x - centered[j].x).powi(2)
+                + (centered[i].y - centered[j].y).powi(2))
+            .sqrt();
+            (sdd + delta[i * n + j] * d, sdsq + d * d)
+        },
+    );
+    let scale = if sum_d_sq > 0. {
+        sum_delta_d / sum_d_sq
+    } else {
+        1.
+    };
+    let targets = centered
+        .iter()
+        .map(|p| Coord {
+            x: p.x * scale,
+            y: p.y * scale,
+        })
+        .collect::<Vec<_>>();
+
+    let result = run_smacof(
+        Dissimilarities {
+            delta: &delta,
+            weights: None,
+            n,
+        },
+        mds_type,
+        targets.clone(),
+        tolerance,
+        max_iter,
+        Some(AnchorPenalty {
+            targets: &targets,
+            weight: anchor_weight,
+        }),
+    )?;
+
+    let mut alignment = procrustes(source_points, &result.points)?;
+    let points = std::mem::take(&mut alignment.points);
+
+    Ok(AnchoredPositions {
+        points,
+        stress: result.stress,
+        n_iter: result.n_iter,
+        converged: result.converged,
+        alignment,
+    })
+}
+
+/// The dissimilarities (a n * n matrix, stored row by row) and their optional
+/// weights (stored the same way, unit weights being used if they are not provided)
+/// used by the SMACOF algorithm.
+pub(crate) struct Dissimilarities<'a> {
+    pub delta: &'a [f64],
+    pub weights: Option<&'a [f64]>,
+    pub n: usize,
+}
+
+/// A penalty pulling each point of the configuration towards its target
+/// (the penalty weight being relative to the weights of the dissimilarities).
+pub(crate) struct AnchorPenalty<'a> {
+    pub targets: &'a [Coord],
+    pub weight: f64,
+}
+
+/// Run the SMACOF algorithm on the given dissimilarities, optionally
+/// penalizing the distance of each point to its target.
+pub(crate) fn run_smacof(
+    dissimilarities: Dissimilarities,
+    mds_type: MdsType,
+    init: Vec<Coord>,
+    tolerance: f64,
+    max_iter: usize,
+    anchor: Option<AnchorPenalty>,
+) -> Result<SmacofResult, Error> {
+    if !(tolerance.is_finite() && tolerance >= 0.) {
+        return Err(Error::InvalidTolerance(tolerance));
+    }
+    if max_iter == 0 {
+        return Err(Error::InvalidMaxIterations(max_iter));
+    }
+    if !init.iter().all(|p| p.x.is_finite() && p.y.is_finite()) {
+        return Err(Error::InvalidInitialConfiguration);
+    }
+    let Dissimilarities { delta, weights, n } = dissimilarities;
+    let w = |i: usize, j: usize| weights.map_or(1., |w| w[i * n + j]);
+
+    // The pairs (i < j) taken into account, with their dissimilarity and weight
+    let pairs = (0..n)
+        .flat_map(|i| ((i + 1)..n).map(move |j| (i, j)))
+        .filter(|&(i, j)| w(i, j) > 0.)
+        .map(|(i, j)| (i, j, delta[i * n + j], w(i, j)))
+        .collect::<Vec<_>>();
+    let sum_w_delta_sq = pairs.iter().map(|p| p.2 * p.2 * p.3).sum::<f64>();
+
+    // Weight of the penalty for each point (so that it is comparable
+    // to the weights of the pairs the point belongs to)
+    let alpha = anchor.as_ref().map_or(0., |a| {
+        a.weight * pairs.iter().map(|p| p.3).sum::<f64>() / n as f64
+    });
+
+    // Inverse of the V + αI matrix (only needed for non-unit weights,
+    // the Guttman transform being simpler with unit weights)
+    let v_inv = weights.map(|_| {
+        let mut v = DMatrix::<f64>::zeros(n, n);
+        for &(i, j, _, wij) in pairs.iter() {
+            v[(i, j)] -= wij;
+            v[(j, i)] -= wij;
+            v[(i, i)] += wij;
+            v[(j, j)] += wij;
+        }
+        if alpha > 0. {
+            (v + DMatrix::<f64>::identity(n, n) * alpha)
+                .try_inverse()
+                .unwrap_or_else(|| DMatrix::<f64>::zeros(n, n))
+        } else {
+            // Use (V + 11'/n)^-1 - 11'/n, which is the pseudo-inverse of V
+            // when the weights connect all the points
+            let ones = DMatrix::<f64>::from_element(n, n, 1. / n as f64);
+            (v + &ones)
+                .try_inverse()
+                .map(|inv| inv - ones)
+                .unwrap_or_else(|| DMatrix::<f64>::zeros(n, n))
+        }
+    });
+
+    let mut x = init;
+    let mut disparities = pairs.iter().map(|p| p.2).collect::<Vec<_>>();
+    let mut distances = pair_distances(&x, &pairs);
+    if distances.iter().all(|&d| d == 0.) {
+        // The Guttman transform can't move the points apart
+        return Err(Error::InvalidInitialConfiguration);
+    }
+    let mut stress = raw_stress(&pairs, &disparities, &distances);
+    let mut converged = false;
+    let mut n_iter = 0;
+
+    for _ in 0..max_iter {
+        n_iter += 1;
+        // Optimal scaling step
+        update_disparities(
+            &pairs,
+            &distances,
+            &mut disparities,
+            mds_type,
+            sum_w_delta_sq,
+        );
+
+        // Guttman transform (with the penalty term if any)
+        let mut bx = vec![Coord { x: 0., y: 0. }; n];
+        for (k, &(i, j, _, wij)) in pairs.iter().enumerate() {
+            if distances[k] == 0. {
+                continue;
+            }
+            let b = wij * disparities[k] / distances[k];
+            let dx = b * (x[i].x - x[j].x);
+            let dy = b * (x[i].y - x[j].y);
+            bx[i].x += dx;
+            bx[i].y += dy;
+            bx[j].x -= dx;
+            bx[j].y -= dy;
+        }
+        if let Some(anchor) = &anchor {
+            for (p, t) in bx.iter_mut().zip(anchor.targets.iter()) {
+                p.x += alpha * t.x;
+                p.y += alpha * t.y;
+            }
+        }
+        x = match &v_inv {
+            // With unit weights, (V + αI)^-1 Y = (Y + 11'Y / α) / (n + α),
+            // which is Y / n when there is no penalty (as 1'Y = 0)
+            None => {
+                let sum = bx.iter().fold(Coord { x: 0., y: 0. }, |acc, p| Coord {
+                    x: acc.x + p.x,
+                    y: acc.y + p.y,
+                });
+                bx.into_iter()
+                    .map(|p| {
+                        if alpha > 0. {
+                            Coord {
+                                x: (p.x + sum.x / alpha) / (n as f64 + alpha),
+                                y: (p.y + sum.y / alpha) / (n as f64 + alpha),
+                            }
+                        } else {
+                            Coord {
+                                x: p.x / n as f64,
+                                y: p.y / n as f64,
+                            }
+                        }
+                    })
+                    .collect()
+            }
+            Some(v_inv) => (0..n)
+                .map(|i| {
+                    let mut p = Coord { x: 0., y: 0. };
+                    for (j, bxj) in bx.iter().enumerate() {
+                        p.x += v_inv[(i, j)] * bxj.x;
+                        p.y += v_inv[(i, j)] * bxj.y;
+                    }
+                    p
+                })
+                .collect(),
+        };
+
+        distances = pair_distances(&x, &pairs);
+        let new_stress = raw_stress(&pairs, &disparities, &distances);
+        let decrease = stress - new_stress;
+        stress = new_stress;
+        if decrease.abs() <= tolerance * stress.max(f64::MIN_POSITIVE) {
+            converged = true;
+            break;
+        }
+    }
+
+    let sum_w_dist_sq = pairs
+        .iter()
+        .zip(distances.iter())
+        .map(|(p, d)| p.3 * d * d)
+        .sum::<f64>();
+    if sum_w_dist_sq == 0. {
+        // All the points collapsed (the dissimilarities being all zero)
+        return Err(Error::DegenerateConfiguration);
+    }
+
+    Ok(SmacofResult {
+        points: x,
+        stress: (stress / sum_w_dist_sq).sqrt(),
+        raw_stress: stress,
+        n_iter,
+        converged,
+    })
+}
+
+fn pair_distances(x: &[Coord], pairs: &[(usize, usize, f64, f64)]) -> Vec<f64> {
+    pairs
+        .iter()
+        .map(|&(i, j, _, _)| ((x[i].x - x[j].x).powi(2) + (x[i].y - x[j].y).powi(2)).sqrt())
+        .collect()
+}
+
+fn raw_stress(pairs: &[(usize, usize, f64, f64)], disparities: &[f64], distances: &[f64]) -> f64 {
+    pairs
+        .iter()
+        .zip(disparities.iter().zip(distances.iter()))
+        .map(|(p, (dh, d))| p.3 * (dh - d).powi(2))
+        .sum()
+}
+
+/// Compute the disparities, given the current distances, according to the type of MDS
+/// (for interval and ordinal MDS, the disparities are normalized so that their
+/// weighted sum of squares is the one of the dissimilarities).
+fn update_disparities(
+    pairs: &[(usize, usize, f64, f64)],
+    distances: &[f64],
+    disparities: &mut [f64],
+    mds_type: MdsType,
+    sum_w_delta_sq: f64,
+) {
+    match mds_type {
+        MdsType::Metric => return,
+        MdsType::Interval => {
+            // Weighted linear regression of the distances on the dissimilarities
+            let sw = pairs.iter().map(|p| p.3).sum::<f64>();
+            let mean_delta = pairs.iter().map(|p| p.3 * p.2).sum::<f64>() / sw;
+            let mean_d = pairs
+                .iter()
+                .zip(distances.iter())
+                .map(|(p, d)| p.3 * d)
+                .sum::<f64>()
+                / sw;
+            let (mut cov, mut var) = (0., 0.);
+            for (p, d) in pairs.iter().zip(distances.iter()) {
+                cov += p.3 * (p.2 - mean_delta) * (d - mean_d);
+                var += p.3 * (p.2 - mean_delta).powi(2);
+            }
+            let b = if var > 0. { (cov / var).max(0.) } else { 0. };
+            let a = mean_d - b * mean_delta;
+            for (dh, p) in disparities.iter_mut().zip(pairs.iter()) {
+                *dh = (a + b * p.2).max(0.);
+            }
+        }
+        MdsType::Ordinal => {
+            // Monotone regression of the distances on the order of the dissimilarities
+            // (ties are broken using the distances - primary approach)
+            let mut order = (0..pairs.len()).collect::<Vec<_>>();
+            order.sort_by(|&a, &b| {
+                pairs[a]
+                    .2
+                    .total_cmp(&pairs[b].2)
+                    .then(distances[a].total_cmp(&distances[b]))
+            });
+            let values = order.iter().map(|&k| distances[k]).collect::<Vec<_>>();
+            let weights = order.iter().map(|&k| pairs[k].3).collect::<Vec<_>>();
+            let fitted = pava(&values, &weights);
+            for (&k, v) in order.iter().zip(fitted) {
+                disparities[k] = v;
+            }
+        }
+    }
+    let sum_w_dh_sq = pairs
+        .iter()
+        .zip(disparities.iter())
+        .map(|(p, dh)| p.3 * dh * dh)
+        .sum::<f64>();
+    if sum_w_dh_sq > 0. {
+        let ratio = (sum_w_delta_sq / sum_w_dh_sq).sqrt();
+        disparities.iter_mut().for_each(|dh| *dh *= ratio);
+    }
+}
+
+/// Weighted isotonic (non-decreasing) regression using the
+/// Pool Adjacent Violators Algorithm.
+fn pava(values: &[f64], weights: &[f64]) -> Vec<f64> {
+    // Blocks of (mean value, total weight, number of values)
+    let mut blocks: Vec<(f64, f64, usize)> = Vec::with_capacity(values.len());
+    for (&v, &w) in values.iter().zip(weights.iter()) {
+        blocks.push((v, w, 1));
+        while blocks.len() > 1 && blocks[blocks.len() - 2].0 > blocks[blocks.len() - 1].0 {
+            let (v2, w2, c2) = blocks.pop().unwrap();
+            let (v1, w1, c1) = blocks.pop().unwrap();
+            let w = w1 + w2;
+            let v = if w > 0. {
+                (v1 * w1 + v2 * w2) / w
+            } else {
+                (v1 + v2) / 2.
+            };
+            blocks.push((v, w, c1 + c2));
+        }
+    }
+    blocks
+        .into_iter()
+        .flat_map(|(v, _, c)| std::iter::repeat_n(v, c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square_durations() -> (Vec<Coord>, Vec<Vec<f64>>) {
+        let pts: Vec<Coord> = vec![
+            Coord { x: 0., y: 0. },
+            Coord { x: 10., y: 0. },
+            Coord { x: 10., y: 10. },
+            Coord { x: 0., y: 10. },
+            Coord { x: 5., y: 3. },
+        ];
+        let durations = pts
+            .iter()
+            .map(|a| {
+                pts.iter()
+                    .map(|b| ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt())
+                    .collect()
+            })
+            .collect();
+        (pts, durations)
+    }
+
+    #[test]
+    fn test_pava() {
+        assert_eq!(
+            pava(&[1., 3., 2., 4.], &[1., 1., 1., 1.]),
+            vec![1., 2.5, 2.5, 4.]
+        );
+    }
+
+    #[test]
+    fn test_smacof_euclidean_durations() {
+        let (pts, durations) = square_durations();
+        let perturbed = pts
+            .iter()
+            .enumerate()
+            .map(|(i, p)| Coord {
+                x: p.x + i as f64,
+                y: p.y - i as f64 * 0.5,
+            })
+            .collect::<Vec<_>>();
+        for mds_type in [MdsType::Metric, MdsType::Interval, MdsType::Ordinal] {
+            let res = smacof(
+                &durations,
+                Symmetrization::Mean,
+                mds_type,
+                Some(&perturbed),
+                1e-10,
+                1000,
+            )
+            .unwrap();
+            assert!(res.stress < 1e-3, "{:?}: {}", mds_type, res.stress);
+        }
+        let mut missing = durations.clone();
+        missing[0][1] = f64::NAN;
+        missing[1][0] = f64::NAN;
+        assert!(matches!(
+            smacof(
+                &missing,
+                Symmetrization::Mean,
+                MdsType::Metric,
+                Some(&perturbed),
+                1e-10,
+                1000,
+            ),
+            Err(Error::MissingDuration(0, 1))
+        ));
+        let run = |init: &[Coord], tolerance: f64, max_iter: usize| {
+            smacof(
+                &durations,
+                Symmetrization::Mean,
+                MdsType::Ordinal,
+                Some(init),
+                tolerance,
+                max_iter,
+            )
+        };
+        assert!(matches!(
+            run(&perturbed, f64::NAN, 1000),
+            Err(Error::InvalidTolerance(_))
+        ));
+        assert!(matches!(
+            run(&perturbed, -1e-10, 1000),
+            Err(Error::InvalidTolerance(_))
+        ));
+        assert!(matches!(
+            run(&perturbed, 1e-10, 0),
+            Err(Error::InvalidMaxIterations(0))
+        ));
+        let mut non_finite = perturbed.clone();
+        non_finite[2].x = f64::NAN;
+        assert!(matches!(
+            run(&non_finite, 1e-10, 1000),
+            Err(Error::InvalidInitialConfiguration)
+        ));
+        let coinciding = vec![Coord { x: 1., y: 1. }; perturbed.len()];
+        assert!(matches!(
+            run(&coinciding, 1e-10, 1000),
+            Err(Error::InvalidInitialConfiguration)
+        ));
+    }
+
+    #[test]
+    fn test_positions_anchored_to_source() {
+        let (pts, durations) = square_durations();
+        // Source points in another CRS (shifted, scaled and rotated by 90°)
+        let source = pts
+            .iter()
+            .map(|p| Coord {
+                x: 1000. - 3. * p.y,
+                y: 500. + 3. * p.x,
+            })
+            .collect::<Vec<_>>();
+        for anchor_weight in [0., 1.] {
+            let res = generate_positions_anchored_to_source(
+                &source,
+                &durations,
+                Symmetrization::Mean,
+                MdsType::Metric,
+                anchor_weight,
+                1e-10,
+                1000,
+            )
+            .unwrap();
+            // The durations are Euclidean, so the source points are recovered
+            for (p, s) in res.points.iter().zip(source.iter()) {
+                assert!((p.x - s.x).abs() < 1e-3 && (p.y - s.y).abs() < 1e-3);
+            }
+            assert!(res.stress < 1e-4);
+        }
+        assert!(matches!(
+            generate_positions_anchored_to_source(
+                &source[1..],
+                &durations,
+                Symmetrization::Mean,
+                MdsType::Metric,
+                1.,
+                1e-10,
+                1000,
+            ),
+            Err(Error::InvalidSourcePointsLength)
+        ));
+        let run = |anchor_weight: f64, tolerance: f64| {
+            generate_positions_anchored_to_source(
+                &source,
+                &durations,
+                Symmetrization::Mean,
+                MdsType::Metric,
+                anchor_weight,
+                tolerance,
+                1000,
+            )
+        };
+        for anchor_weight in [-1., f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                run(anchor_weight, 1e-10),
+                Err(Error::InvalidAnchorWeight(_))
+            ));
+        }
+        assert!(matches!(run(1., -1.), Err(Error::InvalidTolerance(_))));
+    }
+}
@@ -0,0 +1,34 @@
+//! The fixtures shared by the tests of the modules of the crate.
+use crate::grid::Grid;
+use geo_types::Coord;
+
+/// A lattice of 5 x 5 source points, spaced by 10 units (from (0, 0) to (40, 40)).
+pub(crate) fn lattice() -> Vec<Coord> {
+    (0..25)
+        .map(|k| Coord {
+            x: (k % 5) as f64 * 10.,
+            y: (k / 5) as f64 * 10.,
+        })
+        .collect()
+}
+
+/// The image points of the given points, stretched along x and sheared along y
+/// (a smooth deformation that the grid can reproduce).
+pub(crate) fn sheared(points: &[Coord]) -> Vec<Coord> {
+    points
+        .iter()
+        .map(|p| Coord {
+            x: p.x * 1.2,
+            y: p.y + p.x * 0.1,
+        })
+        .collect()
+}
+
+/// The grid (with cells of 5 units) deforming the [`lattice`] into its [`sheared`] image.
+pub(crate) fn sheared_grid() -> Grid {
+    let source = lattice();
+    Grid::builder(&source, &sheared(&source))
+        .resolution(5.)
+        .build()
+        .unwrap()
+}
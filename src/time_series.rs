@@ -0,0 +1,212 @@
+use crate::errors::Error;
+use crate::grid::Grid;
+use crate::grid_builder::GridBuilder;
+use crate::procrustes::procrustes;
+use geo_types::Coord;
+
+/// How the image configurations of a time series of cartograms are aligned
+/// (see [`align_frames`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameAlignment {
+    /// Each configuration is aligned (using a Procrustes analysis) on the source points,
+    /// independently of the other configurations.
+    Source,
+    /// The first configuration is aligned on the source points, then each configuration
+    /// is aligned on the previous (aligned) one, so that the orientation and the scale
+    /// don't jump between consecutive frames.
+    Previous,
+    /// All the configurations are aligned on their consensus (generalized Procrustes
+    /// analysis), which is itself aligned on the source points. The alignment and the
+    /// computation of the consensus are repeated until the consensus moves by less than
+    /// the tolerance (relatively to its size), or for at most `max_iter` iterations.
+    Consensus { tolerance: f64, max_iter: usize },
+}
+
+/// A time series of cartograms sharing the same source points
+/// (see [`cartogram_series`]).
+pub struct CartogramSeries {
+    /// The aligned image points of each frame.
+    pub image_points: Vec<Vec<Coord>>,
+    /// The grid of each frame (all the grids having the same bbox and resolution).
+    pub grids: Vec<Grid>,
+}
+
+impl std::fmt::Debug for CartogramSeries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CartogramSeries")
+            .field("n_frames", &self.grids.len())
+            .finish()
+    }
+}
+
+/// Align the image configurations of a time series (for example the positions
+/// computed with `move_points` or `generate_positions_from_durations` for each hour
+/// of the day) so that their orientation, scale and position are consistent
+/// from one frame to another, and expressed in the CRS of the source points.
+pub fn align_frames(
+    source_points: &[Coord],
+    configurations: &[Vec<Coord>],
+    alignment: FrameAlignment,
+) -> Result<Vec<Vec<Coord>>, Error> {
+    if configurations
+        .iter()
+        .any(|points| points.len() != source_points.len())
+    {
+        return Err(Error::InvalidInputPointsLength);
+    }
+    match alignment {
+        FrameAlignment::Source => configurations
+            .iter()
+            .map(|points| Ok(procrustes(source_points, points)?.points))
+            .collect(),
+        FrameAlignment::Previous => {
+            let mut aligned: Vec<Vec<Coord>> = Vec::with_capacity(configurations.len());
+            for points in configurations {
+                let reference = aligned.last().map_or(source_points, |p| p.as_slice());
+                aligned.push(procrustes(reference, points)?.points);
+            }
+            Ok(aligned)
+        }
+        FrameAlignment::Consensus {
+            tolerance,
+            max_iter,
+        } => {
+            if !(tolerance.is_finite() && tolerance >= 0.) {
+                return Err(Error::InvalidTolerance(tolerance));
+            }
+            let mut consensus = source_points.to_vec();
+            let mut aligned = align_frames(source_points, configurations, FrameAlignment::Source)?;
+            if aligned.is_empty() {
+                return Ok(aligned);
+            }
+            for _ in 0..max_iter {
+                let n = aligned.len() as f64;
+                let mean = (0..source_points.len())
+                    .map(|i| Coord {
+                        x: aligned.iter().map(|p| p[i].x).sum::<f64>() / n,
+                        y: aligned.iter().map(|p| p[i].y).sum::<f64>() / n,
+                    })
+                    .collect::<Vec<_>>();
+                // Keep the consensus in the CRS of the source points
+                let new_consensus = procrustes(source_points, &mean)?.points;
+                let shift = rms_distance(&consensus, &new_consensus);
+                consensus = new_consensus;
+                aligned = configurations
+                    .iter()
+                    .map(|points| Ok(procrustes(&consensus, points)?.points))
+                    .collect::<Result<_, Error>>()?;
+                if shift <= tolerance * rms_distance(&consensus, &centroids(&consensus)) {
+                    break;
+                }
+            }
+            Ok(aligned)
+        }
+    }
+}
+
+/// Align the image configurations of a time series (see [`align_frames`]) and
+/// create the grid of each frame.
+///
+/// The grids are all created with the same builder configuration (set by the
+/// `configure` function, for example `|builder| builder.resolution(5000.)`)
+/// and the same source points, so that they share the same bbox and resolution
+/// and that the sequence of cartograms can be animated smoothly.
+pub fn cartogram_series<F>(
+    source_points: &[Coord],
+    configurations: &[Vec<Coord>],
+    alignment: FrameAlignment,
+    configure: F,
+) -> Result<CartogramSeries, Error>
+where
+    F: for<'a> Fn(GridBuilder<'a>) -> GridBuilder<'a>,
+{
+    let image_points = align_frames(source_points, configurations, alignment)?;
+    let grids = image_points
+        .iter()
+        .map(|points| configure(Grid::builder(source_points, points)).build())
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(CartogramSeries {
+        image_points,
+        grids,
+    })
+}
+
+/// The root mean square distance between two sets of points.
+fn rms_distance(points1: &[Coord], points2: &[Coord]) -> f64 {
+    (points1
+        .iter()
+        .zip(points2.iter())
+        .map(|(p1, p2)| (p1.x - p2.x).powi(2) + (p1.y - p2.y).powi(2))
+        .sum::<f64>()
+        / points1.len() as f64)
+        .sqrt()
+}
+
+/// The centroid of the points, repeated for each point
+/// (to measure the size of the set of points).
+fn centroids(points: &[Coord]) -> Vec<Coord> {
+    let n = points.len() as f64;
+    let centroid = Coord {
+        x: points.iter().map(|p| p.x).sum::<f64>() / n,
+        y: points.iter().map(|p| p.y).sum::<f64>() / n,
+    };
+    vec![centroid; points.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::lattice;
+
+    #[test]
+    fn test_cartogram_series() {
+        let source = lattice();
+        // Relative positions (as returned by a MDS) that are rotated, reflected
+        // and scaled differently from one frame to another
+        let configurations = (0..4)
+            .map(|f| {
+                let angle = f as f64;
+                let scale = 0.01 * (f + 1) as f64;
+                let sign = if f % 2 == 0 { 1. } else { -1. };
+                source
+                    .iter()
+                    .map(|p| {
+                        let (x, y) = (sign * (p.x - 20.), p.y - 20. + 0.05 * (p.x - 20.));
+                        Coord {
+                            x: scale * (x * angle.cos() - y * angle.sin()),
+                            y: scale * (x * angle.sin() + y * angle.cos()),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for alignment in [
+            FrameAlignment::Source,
+            FrameAlignment::Previous,
+            FrameAlignment::Consensus {
+                tolerance: 1e-9,
+                max_iter: 20,
+            },
+        ] {
+            let series = cartogram_series(&source, &configurations, alignment, |builder| {
+                builder.resolution(5.)
+            })
+            .unwrap();
+            assert_eq!(series.grids.len(), 4);
+            // As the frames only differ by a similarity, they are identical once aligned
+            for points in series.image_points.iter().skip(1) {
+                assert!(rms_distance(points, &series.image_points[0]) < 1e-9);
+            }
+            // And close to the source points
+            assert!(rms_distance(&series.image_points[0], &source) < 1.);
+            for grid in series.grids.iter() {
+                let (bbox, first) = (grid.bbox(), series.grids[0].bbox());
+                assert_eq!(
+                    (bbox.xmin, bbox.ymin, bbox.xmax, bbox.ymax),
+                    (first.xmin, first.ymin, first.xmax, first.ymax)
+                );
+                assert_eq!(grid.resolution_xy(), (5., 5.));
+            }
+        }
+    }
+}
@@ -1,5 +1,13 @@
+#[cfg(feature = "moving-points-multipolar")]
+use crate::errors::Error;
 use crate::grid::RMSE;
-use geo_types::{Coord, LineString};
+#[cfg(feature = "moving-points-multipolar")]
+use crate::moving_points_multipolar::{
+    symmetrize_durations, validate_incomplete_durations, Symmetrization,
+};
+use geo_types::Coord;
+#[cfg(feature = "moving-points-unipolar")]
+use geo_types::LineString;
 
 pub(crate) fn distance_sq(p1: &Coord, p2: &Coord) -> f64 {
     (p1.x - p2.x).powi(2) + (p1.y - p2.y).powi(2)
@@ -94,7 +102,7 @@ pub(crate) fn interpolate_line(p1: &Coord, p2: &Coord, distance_along_line: f64)
 pub(crate) fn median(mut series: Vec<f64>) -> f64 {
     series.sort_by(|a, b| a.partial_cmp(b).unwrap());
     let mid = series.len() / 2;
-    if series.len() % 2 == 0 {
+    if series.len().is_multiple_of(2) {
         (series[mid - 1] + series[mid]) / 2.
     } else {
         series[mid]
@@ -102,13 +110,47 @@ pub(crate) fn median(mut series: Vec<f64>) -> f64 {
 }
 
 #[cfg(feature = "moving-points-unipolar")]
-pub(crate) fn buffer_around_point(pt: &Coord, distance: f64, num_segments: usize) -> LineString {
+pub(crate) fn weighted_median(series: &[f64], weights: &[f64]) -> f64 {
+    let mut pairs = series
+        .iter()
+        .copied()
+        .zip(weights.iter().copied())
+        .collect::<Vec<_>>();
+    pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let half = pairs.iter().map(|(_, w)| w).sum::<f64>() / 2.;
+    let mut cumulated = 0.;
+    for (value, weight) in pairs.iter() {
+        cumulated += weight;
+        if cumulated >= half {
+            return *value;
+        }
+    }
+    f64::NAN
+}
+
+/// Angle (in radians, in [0, 2π), counterclockwise from the x axis)
+/// of the vector going from p1 to p2.
+#[cfg(feature = "moving-points-unipolar")]
+pub(crate) fn angle_between(p1: &Coord, p2: &Coord) -> f64 {
+    (p2.y - p1.y)
+        .atan2(p2.x - p1.x)
+        .rem_euclid(2. * std::f64::consts::PI)
+}
+
+/// Create a closed ring around a point, the distance from the point
+/// being given, for each angle (in radians), by the `distance` closure.
+#[cfg(feature = "moving-points-unipolar")]
+pub(crate) fn ring_around_point<F>(pt: &Coord, distance: F, num_segments: usize) -> LineString
+where
+    F: Fn(f64) -> f64,
+{
     let mut coordinates = Vec::with_capacity(num_segments + 1);
 
     for i in 0..num_segments {
         let angle = 2. * std::f64::consts::PI * i as f64 / num_segments as f64;
-        let x = pt.x + distance * angle.cos();
-        let y = pt.y + distance * angle.sin();
+        let d = distance(angle);
+        let x = pt.x + d * angle.cos();
+        let y = pt.y + d * angle.sin();
         coordinates.push(Coord { x, y });
     }
 
@@ -118,12 +160,45 @@ pub(crate) fn buffer_around_point(pt: &Coord, distance: f64, num_segments: usize
     coordinates.into()
 }
 
+#[cfg(feature = "moving-points-multipolar")]
+/// The options used to read a CSV file containing a duration matrix
+/// (see [`read_csv`]).
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// The field delimiter (`b','` by default).
+    pub delimiter: u8,
+    /// The tokens that denote a missing duration (which is read as `NaN`),
+    /// the empty string and `"NA"` by default.
+    pub na_values: Vec<String>,
+    /// Whether the rows can be in a different order than the columns
+    /// (in which case they are reordered to follow the order of the header)
+    /// instead of returning an error (`false` by default).
+    pub reorder_rows: bool,
+}
+
+#[cfg(feature = "moving-points-multipolar")]
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            na_values: vec!["".to_string(), "NA".to_string()],
+            reorder_rows: false,
+        }
+    }
+}
+
 #[cfg(feature = "moving-points-multipolar")]
 /// Read a CSV file containing a duration matrix (so the first line is the header
-/// and the first column is the row names). The header and the row names have to be
-/// identical.
+/// and the first column is the row names). The row names have to be
+/// identical to the header (or to be a permutation of it if the
+/// `reorder_rows` option is set, see [`CsvOptions`]).
 /// The function returns a tuple containing the matrix and the row names.
 ///
+/// An error is returned (with the line and the column of the faulty value)
+/// if the file can't be read, if a value isn't a valid number (or one of the
+/// tokens denoting a missing value), or if the row names don't match the header
+/// (with the label of the first missing row if there are fewer rows than columns).
+///
 /// An example of a valid CSV file for this function is:
 /// ```csv
 /// ,A,B,C
@@ -131,23 +206,338 @@ pub(crate) fn buffer_around_point(pt: &Coord, distance: f64, num_segments: usize
 /// B,1,0,3
 /// C,2,3,0
 /// ```
-pub fn read_csv(file: std::fs::File) -> (Vec<Vec<f64>>, Vec<String>) {
-    let mut rdr = csv::Reader::from_reader(file);
-    let headers = rdr.headers().unwrap();
-    let headers = headers
+pub fn read_csv<R: std::io::Read>(
+    reader: R,
+    options: &CsvOptions,
+) -> Result<(Vec<Vec<f64>>, Vec<String>), Error> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = rdr
+        .headers()?
         .iter()
         .skip(1)
         .map(|x| x.to_string())
         .collect::<Vec<String>>();
-    let mut data = Vec::new();
-    for result in rdr.records() {
-        let record = result.unwrap();
-        let row: Vec<f64> = record
+    if let Some(label) = first_duplicate(&headers) {
+        return Err(Error::DuplicateLabel(label.to_string()));
+    }
+    let mut rows: Vec<Option<Vec<f64>>> = vec![None; headers.len()];
+    for (n_rows, result) in rdr.records().enumerate() {
+        let record = result?;
+        let line = record.position().map_or(0, |p| p.line());
+        let name = record.get(0).unwrap_or_default();
+        let index = if options.reorder_rows {
+            headers.iter().position(|h| h == name)
+        } else {
+            headers.get(n_rows).filter(|h| *h == name).map(|_| n_rows)
+        };
+        let index = match index {
+            Some(index) if rows[index].is_none() => index,
+            _ => return Err(Error::CsvRowNameMismatch(line, name.to_string())),
+        };
+        let row = record
             .iter()
+            .enumerate()
             .skip(1)
-            .map(|x| x.parse::<f64>().unwrap())
-            .collect();
-        data.push(row);
+            .map(|(column, x)| {
+                if options.na_values.iter().any(|na| na == x) {
+                    Ok(f64::NAN)
+                } else {
+                    x.parse::<f64>()
+                        .map_err(|_| Error::CsvInvalidValue(line, column + 1, x.to_string()))
+                }
+            })
+            .collect::<Result<Vec<f64>, Error>>()?;
+        rows[index] = Some(row);
+    }
+    let data = rows
+        .into_iter()
+        .zip(headers.iter())
+        .map(|(row, label)| row.ok_or_else(|| Error::CsvMissingRow(label.to_string())))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok((data, headers))
+}
+
+#[cfg(feature = "moving-points-multipolar")]
+/// Reorder a duration matrix (labelled by the given `labels`, as returned
+/// by [`read_csv`]) so that its rows and columns follow the order of the
+/// given point identifiers (for example the identifiers of the source points).
+///
+/// This guarantees that the `i`-th row and column of the returned matrix
+/// correspond to the `i`-th point. The labels that don't match any point
+/// identifier are ignored, while an error is returned if a point identifier
+/// doesn't match any label or if an identifier (or a label) is duplicated.
+pub fn join_durations_to_points<S: AsRef<str>>(
+    durations: &[Vec<f64>],
+    labels: &[String],
+    point_ids: &[S],
+) -> Result<Vec<Vec<f64>>, Error> {
+    if durations.len() != labels.len() || durations.iter().any(|row| row.len() != labels.len()) {
+        return Err(Error::DurationMatrixNotSquare);
+    }
+    if let Some(label) = first_duplicate(labels) {
+        return Err(Error::DuplicateLabel(label.to_string()));
+    }
+    if let Some(id) = first_duplicate(point_ids) {
+        return Err(Error::DuplicateLabel(id.to_string()));
+    }
+    let indexes = point_ids
+        .iter()
+        .map(|id| {
+            labels
+                .iter()
+                .position(|l| l == id.as_ref())
+                .ok_or_else(|| Error::UnmatchedLabel(id.as_ref().to_string()))
+        })
+        .collect::<Result<Vec<usize>, Error>>()?;
+    Ok(indexes
+        .iter()
+        .map(|&i| indexes.iter().map(|&j| durations[i][j]).collect())
+        .collect())
+}
+
+#[cfg(feature = "moving-points-multipolar")]
+/// Read a CSV file containing durations in long format, i.e. one line per
+/// origin-destination pair, as exported by most routing tools
+/// (so the first line is the header and the first three columns are the identifier
+/// of the origin, the identifier of the destination and the duration, any other column being ignored).
+/// The function returns the list of `(origin, destination, duration)` entries,
+/// which can be assembled into a duration matrix with [`durations_from_od_table`].
+///
+/// Only the `delimiter` and `na_values` options are used (see [`CsvOptions`]),
+/// the missing durations being read as `NaN`.
+/// An error is returned (with the line and the column of the faulty value)
+/// if the file can't be read, if a line has fewer than three fields
+/// or if a duration isn't a valid number.
+///
+/// An example of a valid CSV file for this function is:
+/// ```csv
+/// origin,destination,duration
+/// A,B,1
+/// A,C,2
+/// B,C,3
+/// ```
+pub fn read_od_table<R: std::io::Read>(
+    reader: R,
+    options: &CsvOptions,
+) -> Result<Vec<(String, String, f64)>, Error> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(reader);
+    let mut entries = Vec::new();
+    for result in rdr.records() {
+        let record = result?;
+        let line = record.position().map_or(0, |p| p.line());
+        if record.len() < 3 {
+            return Err(Error::CsvMissingFields(line, record.len(), 3));
+        }
+        let value = &record[2];
+        let duration = if options.na_values.iter().any(|na| na == value) {
+            f64::NAN
+        } else {
+            value
+                .parse::<f64>()
+                .map_err(|_| Error::CsvInvalidValue(line, 3, value.to_string()))?
+        };
+        entries.push((record[0].to_string(), record[1].to_string(), duration));
+    }
+    Ok(entries)
+}
+
+#[cfg(feature = "moving-points-multipolar")]
+/// The duration matrix assembled from a long-format table
+/// by the [`durations_from_od_table`] function.
+#[derive(Debug, Clone)]
+pub struct OdDurations {
+    /// The duration matrix (in the order of the given identifiers), the pairs
+    /// missing from the table being NaN (unless they were filled by the symmetrization).
+    pub durations: Vec<Vec<f64>>,
+    /// The (origin, destination) pairs, as indexes in the list of identifiers,
+    /// that are missing from the table (the diagonal, set to 0, excepted).
+    pub missing: Vec<(usize, usize)>,
+    /// The (origin, destination) pairs, as indexes in the list of identifiers,
+    /// that appear more than once in the table (only their first duration is kept).
+    pub duplicates: Vec<(usize, usize)>,
+}
+
+#[cfg(feature = "moving-points-multipolar")]
+/// Assemble a duration matrix from the entries of a long-format table
+/// (`(origin, destination, duration)`, as returned by [`read_od_table`]),
+/// following the order of the given point identifiers (for example the
+/// identifiers of the source points).
+///
+/// The entries whose origin or destination doesn't match any identifier are ignored.
+/// The pairs that are missing from the table (or whose duration is missing,
+/// i.e. NaN, such as the NA values read by [`read_od_table`]) are set to NaN and the pairs that
+/// appear more than once are only taken into account once, both being reported
+/// in the result (see [`OdDurations`]). The diagonal is set to 0 when it is missing.
+///
+/// If a [`Symmetrization`](crate::Symmetrization) policy is given, the matrix is
+/// made symmetric (see [`symmetrize_durations`](crate::symmetrize_durations)),
+/// which also fills the pairs that are only known in one direction.
+///
+/// The resulting matrix can be used directly with
+/// [`generate_positions_from_durations`](crate::generate_positions_from_durations)
+/// (or with [`generate_positions_from_incomplete_durations`](crate::generate_positions_from_incomplete_durations)
+/// if some durations are missing), while one of its rows contains the durations
+/// from a single origin, as expected by `move_points`.
+///
+/// An error is returned if an identifier is duplicated or if the table contains
+/// a negative duration (or a non-zero duration from a point to itself).
+pub fn durations_from_od_table<S: AsRef<str>, T: AsRef<str>>(
+    entries: &[(S, S, f64)],
+    point_ids: &[T],
+    symmetrization: Option<Symmetrization>,
+) -> Result<OdDurations, Error> {
+    if let Some(id) = first_duplicate(point_ids) {
+        return Err(Error::DuplicateLabel(id.to_string()));
+    }
+    let n = point_ids.len();
+    let index = point_ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_ref(), i))
+        .collect::<std::collections::HashMap<_, _>>();
+    let mut durations = vec![vec![f64::NAN; n]; n];
+    let mut known = vec![vec![false; n]; n];
+    let mut duplicates = Vec::new();
+    let mut duplicated = std::collections::HashSet::new();
+    for (origin, destination, duration) in entries.iter() {
+        if let (Some(&i), Some(&j)) = (index.get(origin.as_ref()), index.get(destination.as_ref()))
+        {
+            if duration.is_nan() {
+                continue;
+            }
+            if known[i][j] {
+                if duplicated.insert((i, j)) {
+                    duplicates.push((i, j));
+                }
+                continue;
+            }
+            known[i][j] = true;
+            durations[i][j] = *duration;
+        }
+    }
+    let mut missing = Vec::new();
+    for (i, row) in durations.iter_mut().enumerate() {
+        if !known[i][i] {
+            row[i] = 0.;
+        }
+        missing.extend((0..n).filter(|&j| j != i && !known[i][j]).map(|j| (i, j)));
+    }
+    let durations = match symmetrization {
+        Some(policy) => symmetrize_durations(&durations, policy)?,
+        None => {
+            validate_incomplete_durations(&durations)?;
+            durations
+        }
+    };
+    Ok(OdDurations {
+        durations,
+        missing,
+        duplicates,
+    })
+}
+
+#[cfg(feature = "moving-points-multipolar")]
+/// Return the first duplicated value of a list of labels, if any.
+fn first_duplicate<S: AsRef<str>>(labels: &[S]) -> Option<&str> {
+    let mut seen = std::collections::HashSet::new();
+    labels.iter().map(|l| l.as_ref()).find(|l| !seen.insert(*l))
+}
+
+#[cfg(all(test, feature = "moving-points-multipolar"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_csv_and_join() {
+        let data = "id;A;B;C\nB;1;0;NA\nA;0;1;2\nC;2;3;0\n";
+        assert!(matches!(
+            read_csv(
+                data.as_bytes(),
+                &CsvOptions {
+                    delimiter: b';',
+                    ..Default::default()
+                }
+            ),
+            Err(Error::CsvRowNameMismatch(2, _))
+        ));
+        let options = CsvOptions {
+            delimiter: b';',
+            reorder_rows: true,
+            ..Default::default()
+        };
+        let (durations, labels) = read_csv(data.as_bytes(), &options).unwrap();
+        assert_eq!(labels, vec!["A", "B", "C"]);
+        assert_eq!(durations[0], vec![0., 1., 2.]);
+        assert!(durations[1][2].is_nan());
+
+        let joined = join_durations_to_points(&durations, &labels, &["C", "A"]).unwrap();
+        assert_eq!(joined, vec![vec![0., 2.], vec![2., 0.]]);
+        assert!(matches!(
+            join_durations_to_points(&durations, &labels, &["A", "D"]),
+            Err(Error::UnmatchedLabel(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_csv_invalid_value() {
+        let data = ",A,B\nA,0,x\nB,1,0\n";
+        assert!(matches!(
+            read_csv(data.as_bytes(), &CsvOptions::default()),
+            Err(Error::CsvInvalidValue(2, 3, _))
+        ));
+        let data = ",A,B,C\nA,0,1,2\nB,1,0,3\n";
+        assert!(matches!(
+            read_csv(data.as_bytes(), &CsvOptions::default()),
+            Err(Error::CsvMissingRow(label)) if label == "C"
+        ));
+        // A missing column is reported by the CSV reader (with its position)
+        let data = ",A,B\nA,0,1\nB,1\n";
+        match read_csv(data.as_bytes(), &CsvOptions::default()) {
+            Err(Error::CsvRead(e)) => {
+                assert_eq!(e.position().map(|p| p.line()), Some(3));
+                assert!(std::error::Error::source(&Error::CsvRead(e)).is_some());
+            }
+            _ => panic!("a missing column must be reported"),
+        }
+        let data = "origin,destination,duration\nA,B,1\nA,C\n";
+        assert!(matches!(
+            read_od_table(data.as_bytes(), &CsvOptions::default()),
+            Err(Error::CsvMissingFields(3, 2, 3))
+        ));
+    }
+
+    #[test]
+    fn test_durations_from_od_table() {
+        let data = "origin,destination,duration\nA,B,1\nB,A,3\nA,C,2\nA,B,5\nC,D,4\n";
+        let entries = read_od_table(data.as_bytes(), &CsvOptions::default()).unwrap();
+        assert_eq!(entries.len(), 5);
+
+        let res = durations_from_od_table(&entries, &["A", "B", "C"], None).unwrap();
+        assert_eq!(res.durations[0], vec![0., 1., 2.]);
+        assert!(res.durations[2][0].is_nan());
+        assert_eq!(res.missing, vec![(1, 2), (2, 0), (2, 1)]);
+        assert_eq!(res.duplicates, vec![(0, 1)]);
+
+        let res = durations_from_od_table(&entries, &["A", "B", "C"], Some(Symmetrization::Mean))
+            .unwrap();
+        assert_eq!(res.durations[1][0], 2.);
+        assert_eq!(res.durations[2][0], 2.);
+        assert!(res.durations[1][2].is_nan());
+
+        // The NA values are missing durations (on the diagonal too)
+        let data = "origin,destination,duration\nA,B,NA\nA,A,NA\nB,A,2\n";
+        let entries = read_od_table(data.as_bytes(), &CsvOptions::default()).unwrap();
+        let res = durations_from_od_table(&entries, &["A", "B"], None).unwrap();
+        assert_eq!(res.missing, vec![(0, 1)]);
+        assert!(res.durations[0][1].is_nan());
+        assert_eq!(res.durations[0][0], 0.);
+        assert!(res.duplicates.is_empty());
     }
-    (data, headers)
 }
@@ -0,0 +1,232 @@
+use geo_types::Coord;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The minimum number of homologous points needed to create a grid.
+pub const MIN_POINTS: usize = 3;
+
+/// One of the two sets of homologous points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointSet {
+    /// The source points.
+    Source,
+    /// The image points.
+    Image,
+}
+
+impl fmt::Display for PointSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointSet::Source => write!(f, "source"),
+            PointSet::Image => write!(f, "image"),
+        }
+    }
+}
+
+/// An issue found in a set of homologous points (see [`validate_points`]).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// The number of source points and image points differ.
+    LengthMismatch(usize, usize),
+    /// There are fewer points than needed (the number of points is given).
+    TooFewPoints(usize),
+    /// The source point at the given index has NaN or infinite coordinates.
+    NonFiniteSourcePoint(usize),
+    /// The image point at the given index has NaN or infinite coordinates.
+    NonFiniteImagePoint(usize),
+    /// The points of the given set all have the same x coordinate.
+    ZeroWidthExtent(PointSet),
+    /// The points of the given set all have the same y coordinate.
+    ZeroHeightExtent(PointSet),
+    /// The source points at the given indexes are identical but their image points differ
+    /// (this is only a warning: the grid can be created, but it can't fit both image points).
+    ConflictingDuplicates(usize, usize),
+}
+
+impl ValidationIssue {
+    /// Whether the issue prevents the creation of the grid
+    /// (otherwise it is only a warning).
+    pub fn is_error(&self) -> bool {
+        !matches!(self, ValidationIssue::ConflictingDuplicates(_, _))
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::LengthMismatch(n1, n2) => write!(
+                f,
+                "the number of source points ({}) and image points ({}) differ",
+                n1, n2
+            ),
+            ValidationIssue::TooFewPoints(n) => {
+                write!(f, "at least {} points are needed (found {})", MIN_POINTS, n)
+            }
+            ValidationIssue::NonFiniteSourcePoint(i) => {
+                write!(f, "the source point {} has non-finite coordinates", i)
+            }
+            ValidationIssue::NonFiniteImagePoint(i) => {
+                write!(f, "the image point {} has non-finite coordinates", i)
+            }
+            ValidationIssue::ZeroWidthExtent(set) => {
+                write!(f, "the extent of the {} points has a zero width", set)
+            }
+            ValidationIssue::ZeroHeightExtent(set) => {
+                write!(f, "the extent of the {} points has a zero height", set)
+            }
+            ValidationIssue::ConflictingDuplicates(i, j) => write!(
+                f,
+                "the source points {} and {} are identical but their image points differ",
+                i, j
+            ),
+        }
+    }
+}
+
+/// The issues found in a set of homologous points (see [`validate_points`]).
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// All the issues found (errors and warnings).
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Whether the report contains an issue preventing the creation of the grid.
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|issue| issue.is_error())
+    }
+
+    /// The issues preventing the creation of the grid.
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|issue| issue.is_error())
+    }
+
+    /// The issues that don't prevent the creation of the grid.
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|issue| !issue.is_error())
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (k, issue) in self.issues.iter().enumerate() {
+            if k > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check a set of homologous points before creating a grid with them
+/// (this is done by [`Grid::new`](crate::Grid::new) and
+/// [`GridBuilder::build`](crate::GridBuilder::build), which return an error
+/// if the report contains errors).
+///
+/// The report lists the points with NaN or infinite coordinates,
+/// the identical source points whose image points differ, the extents of zero width or
+/// height (that would make the interpolation produce NaN values) and whether there are
+/// fewer points than needed.
+/// Note that points aligned along another direction (such as a diagonal) have an extent
+/// of non-zero width and height and are thus not reported.
+pub fn validate_points(source_points: &[Coord], image_points: &[Coord]) -> ValidationReport {
+    let mut issues = Vec::new();
+    if source_points.len() != image_points.len() {
+        issues.push(ValidationIssue::LengthMismatch(
+            source_points.len(),
+            image_points.len(),
+        ));
+    }
+    if source_points.len() < MIN_POINTS {
+        issues.push(ValidationIssue::TooFewPoints(source_points.len()));
+    }
+    let is_finite = |p: &Coord| p.x.is_finite() && p.y.is_finite();
+    issues.extend(
+        source_points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !is_finite(p))
+            .map(|(i, _)| ValidationIssue::NonFiniteSourcePoint(i)),
+    );
+    issues.extend(
+        image_points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !is_finite(p))
+            .map(|(i, _)| ValidationIssue::NonFiniteImagePoint(i)),
+    );
+
+    for (points, set) in [
+        (source_points, PointSet::Source),
+        (image_points, PointSet::Image),
+    ] {
+        let mut finite = points.iter().filter(|p| is_finite(p));
+        if let Some(first) = finite.next() {
+            let (mut same_x, mut same_y) = (true, true);
+            for p in finite {
+                same_x &= p.x == first.x;
+                same_y &= p.y == first.y;
+            }
+            if same_x {
+                issues.push(ValidationIssue::ZeroWidthExtent(set));
+            }
+            if same_y {
+                issues.push(ValidationIssue::ZeroHeightExtent(set));
+            }
+        }
+    }
+
+    // Identical source points with different image points
+    // (adding 0 to normalize -0 to 0 before comparing the bits)
+    let mut seen: HashMap<(u64, u64), usize> = HashMap::with_capacity(source_points.len());
+    for (i, (p, img)) in source_points.iter().zip(image_points.iter()).enumerate() {
+        if !is_finite(p) {
+            continue;
+        }
+        let key = ((p.x + 0.).to_bits(), (p.y + 0.).to_bits());
+        match seen.get(&key) {
+            Some(&j) if image_points[j] != *img => {
+                issues.push(ValidationIssue::ConflictingDuplicates(j, i));
+            }
+            Some(_) => {}
+            None => {
+                seen.insert(key, i);
+            }
+        }
+    }
+
+    ValidationReport { issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_points() {
+        let source = vec![
+            Coord { x: 0., y: 0. },
+            Coord { x: 1., y: 0. },
+            Coord { x: 0., y: 0. },
+            Coord { x: 2., y: f64::NAN },
+        ];
+        let image = vec![
+            Coord { x: 0., y: 0. },
+            Coord { x: 1., y: 1. },
+            Coord { x: 0., y: 1. },
+            Coord { x: 2., y: 2. },
+        ];
+        let report = validate_points(&source, &image);
+        assert!(report.has_errors());
+        assert_eq!(
+            report.issues,
+            vec![
+                ValidationIssue::NonFiniteSourcePoint(3),
+                ValidationIssue::ZeroHeightExtent(PointSet::Source),
+                ValidationIssue::ConflictingDuplicates(0, 2),
+            ]
+        );
+        assert_eq!(report.warnings().count(), 1);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::errors::Error;
+use crate::grid::Grid;
+use crate::raster::Raster;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The resampling method used to compute the value of the pixels of a
+/// warped raster (see [`Grid::warp_raster`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resampling {
+    /// The value of the pixel containing the position (suitable for categorical data).
+    Nearest,
+    /// The bilinear interpolation of the 4 pixels around the position.
+    Bilinear,
+    /// The bicubic (Catmull-Rom) interpolation of the 16 pixels around the position.
+    Bicubic,
+}
+
+impl Grid {
+    /// Deform a raster (such as a scanned map, a hillshade or a satellite image)
+    /// through the grid.
+    ///
+    /// The warped raster has the given dimensions and geotransform (see [`Raster`]),
+    /// in the cartogram space. Each of its pixels is mapped back through the
+    /// inverse of the deformation (see [`Grid::get_inverse_interp_point`]) into
+    /// the source raster, whose value is then computed with the given resampling method.
+    ///
+    /// The pixels falling outside the transformed grid or outside the source raster
+    /// are missing values (the nodata value of the source raster, or NaN if it has none).
+    pub fn warp_raster(
+        &self,
+        raster: &Raster,
+        width: usize,
+        height: usize,
+        geotransform: [f64; 6],
+        resampling: Resampling,
+    ) -> Result<Raster, Error> {
+        self.warp_raster_with(
+            raster,
+            width,
+            height,
+            geotransform,
+            resampling,
+            |n, pixel| (0..n).map(pixel).collect(),
+        )
+    }
+
+    #[cfg(feature = "parallel")]
+    /// Deform a raster through the grid, computing the positions of the pixels
+    /// in parallel using rayon (see [`Grid::warp_raster`]).
+    pub fn warp_raster_par(
+        &self,
+        raster: &Raster,
+        width: usize,
+        height: usize,
+        geotransform: [f64; 6],
+        resampling: Resampling,
+    ) -> Result<Raster, Error> {
+        self.warp_raster_with(
+            raster,
+            width,
+            height,
+            geotransform,
+            resampling,
+            |n, pixel| (0..n).into_par_iter().map(pixel).collect(),
+        )
+    }
+
+    /// Deform a raster through the grid, the positions of the `n` pixels of the
+    /// warped raster in the source raster being computed by the given function
+    /// (which maps the pixel function over `0..n`, sequentially or in parallel).
+    fn warp_raster_with<F>(
+        &self,
+        raster: &Raster,
+        width: usize,
+        height: usize,
+        geotransform: [f64; 6],
+        resampling: Resampling,
+        positions: F,
+    ) -> Result<Raster, Error>
+    where
+        F: FnOnce(usize, &(dyn Fn(usize) -> Option<(f64, f64)> + Sync)) -> Vec<Option<(f64, f64)>>,
+    {
+        let mut output = Raster::new(
+            width,
+            height,
+            raster.n_bands,
+            geotransform,
+            raster.nodata,
+            vec![raster.nodata.unwrap_or(f64::NAN); raster.n_bands * width * height],
+        )?;
+        raster.check()?;
+        let index = self.cell_index();
+        let positions = positions(width * height, &|k| {
+            self.source_pixel(&index, raster, &output, k)
+        });
+        fill_warped(raster, &mut output, &positions, resampling);
+        Ok(output)
+    }
+
+    /// The position (row, column) in the source raster of the center
+    /// of the k-th pixel of the warped raster, if any.
+    fn source_pixel(
+        &self,
+        index: &crate::grid::CellIndex,
+        raster: &Raster,
+        output: &Raster,
+        k: usize,
+    ) -> Option<(f64, f64)> {
+        let (row, column) = (k / output.width, k % output.width);
+        let point = output.pixel_to_coord(row as f64 + 0.5, column as f64 + 0.5);
+        let source = self.get_inverse_interp_point_indexed(index, &point)?;
+        let (row, column) = raster.coord_to_pixel(&source);
+        if row >= 0. && column >= 0. && row < raster.height as f64 && column < raster.width as f64 {
+            Some((row, column))
+        } else {
+            None
+        }
+    }
+}
+
+/// Fill the bands of the warped raster from the positions of its pixels
+/// in the source raster.
+fn fill_warped(
+    raster: &Raster,
+    output: &mut Raster,
+    positions: &[Option<(f64, f64)>],
+    resampling: Resampling,
+) {
+    let size = output.width * output.height;
+    for band in 0..raster.n_bands {
+        for (k, position) in positions.iter().enumerate() {
+            if let Some((row, column)) = position {
+                if let Some(value) = resample(raster, band, *row, *column, resampling) {
+                    output.data[band * size + k] = value;
+                }
+            }
+        }
+    }
+}
+
+/// Compute the value of the band at the given position (in pixels) of the raster.
+/// The bilinear and bicubic interpolations fall back to the nearest pixel
+/// when one of the pixels they use is missing.
+fn resample(
+    raster: &Raster,
+    band: usize,
+    row: f64,
+    column: f64,
+    resampling: Resampling,
+) -> Option<f64> {
+    let nearest = raster.get(band, row.floor() as usize, column.floor() as usize);
+    let nearest = if raster.is_nodata(nearest) {
+        None
+    } else {
+        Some(nearest)
+    };
+    // The position relatively to the centers of the pixels
+    let (r, c) = (row - 0.5, column - 0.5);
+    let (r0, c0) = (r.floor(), c.floor());
+    let (dr, dc) = (r - r0, c - c0);
+    let pixel = |i: isize, j: isize| {
+        let i = (r0 as isize + i).clamp(0, raster.height as isize - 1) as usize;
+        let j = (c0 as isize + j).clamp(0, raster.width as isize - 1) as usize;
+        raster.get(band, i, j)
+    };
+    let (offsets, weights_r, weights_c) = match resampling {
+        Resampling::Nearest => return nearest,
+        Resampling::Bilinear => (0..=1, vec![1. - dr, dr], vec![1. - dc, dc]),
+        Resampling::Bicubic => (-1..=2, cubic_weights(dr), cubic_weights(dc)),
+    };
+    let start = *offsets.start();
+    let mut value = 0.;
+    for i in offsets.clone() {
+        for j in offsets.clone() {
+            let v = pixel(i, j);
+            if raster.is_nodata(v) {
+                return nearest;
+            }
+            value += weights_r[(i - start) as usize] * weights_c[(j - start) as usize] * v;
+        }
+    }
+    Some(value)
+}
+
+/// The weights of the 4 pixels around a position (at distance t from the second one)
+/// for the Catmull-Rom cubic interpolation.
+fn cubic_weights(t: f64) -> Vec<f64> {
+    let (t2, t3) = (t * t, t * t * t);
+    vec![
+        (-t3 + 2. * t2 - t) / 2.,
+        (3. * t3 - 5. * t2 + 2.) / 2.,
+        (-3. * t3 + 4. * t2 + t) / 2.,
+        (t3 - t2) / 2.,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{lattice, sheared_grid};
+    use geo_types::Coord;
+
+    #[test]
+    fn test_warp_raster() {
+        let source = lattice();
+        // The image points are the source points, so the grid isn't deformed
+        let grid = Grid::builder(&source, &source)
+            .resolution(5.)
+            .build()
+            .unwrap();
+        // A raster whose values are a linear function of the coordinates
+        let data = (0..40 * 40)
+            .map(|k| ((k % 40) + 2 * (k / 40)) as f64)
+            .collect::<Vec<_>>();
+        let raster = Raster::new(40, 40, 1, [0., 1., 0., 40., 0., -1.], None, data).unwrap();
+        let gt = raster.geotransform;
+        for resampling in [
+            Resampling::Nearest,
+            Resampling::Bilinear,
+            Resampling::Bicubic,
+        ] {
+            let warped = grid.warp_raster(&raster, 40, 40, gt, resampling).unwrap();
+            // The warped raster is the source raster (the interpolations being exact
+            // for a linear function)
+            for (row, column) in [(5, 5), (20, 31), (38, 1)] {
+                assert!((warped.get(0, row, column) - raster.get(0, row, column)).abs() < 1e-6);
+            }
+        }
+        // Outside the transformed grid
+        let outside = grid
+            .warp_raster(
+                &raster,
+                2,
+                2,
+                [1000., 1., 0., 1000., 0., -1.],
+                Resampling::Nearest,
+            )
+            .unwrap();
+        assert!(outside.data.iter().all(|v| v.is_nan()));
+
+        // Through a deformed grid (x' = 1.2 x, y' = y + 0.1 x), the pixel centered on
+        // (30.5, 20.5) in the cartogram comes from (about) the source position
+        // (30.5 / 1.2, 20.5 - 3.05 / 1.2)
+        let grid = sheared_grid();
+        let warped = grid
+            .warp_raster(
+                &raster,
+                48,
+                44,
+                [0., 1., 0., 44., 0., -1.],
+                Resampling::Bilinear,
+            )
+            .unwrap();
+        let source = grid
+            .get_inverse_interp_point(&Coord { x: 30.5, y: 20.5 })
+            .unwrap();
+        assert!(
+            (source.x - 30.5 / 1.2).abs() < 0.1 && (source.y - (20.5 - 3.05 / 1.2)).abs() < 0.1
+        );
+        let (row, column) = raster.coord_to_pixel(&source);
+        let expected = (column - 0.5) + 2. * (row - 0.5);
+        assert!((warped.get(0, 23, 30) - expected).abs() < 1e-6);
+        // The parallel version gives the same raster (some pixels being outside the grid)
+        #[cfg(feature = "parallel")]
+        {
+            let gt = [0., 1., 0., 44., 0., -1.];
+            let par = grid
+                .warp_raster_par(&raster, 48, 44, gt, Resampling::Bilinear)
+                .unwrap();
+            let bits = |r: &Raster| r.data.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+            assert_eq!(bits(&par), bits(&warped));
+        }
+    }
+
+    #[test]
+    fn test_cell_index() {
+        let source = lattice();
+        let image = source
+            .iter()
+            .map(|p| Coord {
+                x: p.x * 1.2 + (p.y / 10.).powi(2),
+                y: p.y + p.x * 0.1,
+            })
+            .collect::<Vec<_>>();
+        let grid = Grid::builder(&source, &image).build().unwrap();
+        let index = grid.cell_index();
+        for k in 0..400 {
+            let p = Coord {
+                x: -5. + (k % 20) as f64 * 4.,
+                y: -5. + (k / 20) as f64 * 4.,
+            };
+            assert_eq!(
+                grid.get_inverse_interp_point_indexed(&index, &p),
+                grid.get_inverse_interp_point(&p).ok()
+            );
+        }
+    }
+}